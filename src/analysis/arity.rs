@@ -0,0 +1,127 @@
+//! Compares the number of arguments at each call against the function being called, without running the program.
+use crate::eval::native::{Arity, NativeRegistry};
+use crate::parser::cst::{SyntaxKind, SyntaxNode};
+use crate::token::token::Token;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+/// A call whose number of arguments the function it calls does not accept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArityMismatch {
+    pub name: String,
+    pub expected: Arity,
+    pub found: usize,
+    /// Byte range of the call.
+    pub call: Range<usize>,
+    /// Byte range of the function's name and parameters, or None for a native function.
+    pub definition: Option<Range<usize>>,
+}
+
+impl fmt::Display for ArityMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} takes {} argument{} but {} {} given",
+            self.name,
+            self.expected,
+            if self.expected == Arity::Exact(1) {
+                ""
+            } else {
+                "s"
+            },
+            self.found,
+            if self.found == 1 { "was" } else { "were" }
+        )
+    }
+}
+
+/// What a name is bound to across the whole program.
+enum Binding {
+    /// Defined once with uwu and never bound any other way.
+    Function {
+        parameters: usize,
+        span: Range<usize>,
+    },
+    /// Bound more than once or to something other than a function, so calls to it can't be checked.
+    Unknown,
+}
+
+/// Checks every call in a program whose callee is known. Calls to names bound by anything other than a single uwu definition are skipped, as are names the program never binds that aren't natives.
+///
+/// # Examples
+/// ```ignore
+/// let tree = parse_cst("uwu f(a) { sugoi a; }; f(1, 2);").unwrap();
+/// let mismatches = check_arity(&tree, &NativeRegistry::with_builtins());
+/// assert_eq!(mismatches[0].to_string(), "f takes 1 argument but 2 were given");
+/// assert_eq!(mismatches[0].definition, Some(0..8));
+/// ```
+pub fn check_arity(tree: &SyntaxNode, natives: &NativeRegistry) -> Vec<ArityMismatch> {
+    let nodes = tree.descendants();
+    let mut bindings: HashMap<String, Binding> = HashMap::new();
+    let mut bind = |name: &Token, binding: Binding| {
+        if let Token::IDENT(name) = name {
+            // A second binding of any kind makes the name ambiguous.
+            bindings
+                .entry(name.clone())
+                .and_modify(|existing| *existing = Binding::Unknown)
+                .or_insert(binding);
+        }
+    };
+    for node in nodes.iter() {
+        match node.kind() {
+            SyntaxKind::Define => {
+                let parameters = node.nodes().next();
+                if let (Some(name), Some(parameters)) = (node.names().next(), parameters) {
+                    bind(
+                        &name.token,
+                        Binding::Function {
+                            parameters: parameters.names().count(),
+                            span: node.span().start..parameters.span().end,
+                        },
+                    );
+                }
+            }
+            SyntaxKind::Let | SyntaxKind::For | SyntaxKind::ParameterList => {
+                for name in node.names() {
+                    bind(&name.token, Binding::Unknown);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let mut mismatches = vec![];
+    for node in nodes.iter().filter(|node| node.kind() == SyntaxKind::Call) {
+        let mut parts = node.nodes();
+        let (name, arguments) = match (
+            parts.next().and_then(|callee| callee.names().next()),
+            parts.next(),
+        ) {
+            (Some(name), Some(arguments)) => (name, arguments),
+            _ => continue,
+        };
+        let name = match &name.token {
+            Token::IDENT(name) => name,
+            _ => continue,
+        };
+        let (expected, definition) = match (bindings.get(name), natives.get(name)) {
+            (Some(Binding::Function { parameters, span }), _) => {
+                (Arity::Exact(*parameters), Some(span.clone()))
+            }
+            (None, Some(native)) => (native.arity, None),
+            _ => continue,
+        };
+        let found = arguments.nodes().count();
+        if !expected.accepts(found) {
+            mismatches.push(ArityMismatch {
+                name: name.clone(),
+                expected,
+                found,
+                call: node.span(),
+                definition,
+            });
+        }
+    }
+    mismatches
+}
@@ -0,0 +1,26 @@
+//! How serious the problems found by static checks are, and where in the source they are.
+use std::fmt;
+
+/// Errors mean the program would fail if it got that far, warnings point at code that probably does not do what was meant.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// Finds the line and column, both counting from 1, of a byte offset into the source.
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&chr| chr != '\n').count() + 1;
+    (line, column)
+}
@@ -0,0 +1,326 @@
+//! Lints point out code that runs but probably doesn't do what was meant.
+//!
+//! Any rule can be turned off for a line by putting a comment on it, or on the line before, that names the rules to allow:
+//! ```text
+//! /* lint: allow(unused-binding) */
+//! owo unused = 1;
+//! ```
+use crate::analysis::diagnostic::{line_col, Severity};
+use crate::lexer::trivia::{SyntaxToken, Trivia};
+use crate::parser::ast::Expr;
+use crate::parser::cst::{SyntaxElement, SyntaxKind, SyntaxNode};
+use crate::token::token::Token;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+
+/// A check the linter runs, identified by the name used to allow it.
+#[derive(Debug, PartialEq)]
+pub struct Rule {
+    pub id: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+}
+
+pub const UNUSED_BINDING: Rule = Rule {
+    id: "unused-binding",
+    severity: Severity::Warning,
+    description: "a variable defined with owo is never used",
+};
+pub const UNREACHABLE_CODE: Rule = Rule {
+    id: "unreachable-code",
+    severity: Severity::Warning,
+    description: "a statement comes after sugoi, bweak or continyue in the same block",
+};
+pub const CONSTANT_CONDITION: Rule = Rule {
+    id: "constant-condition",
+    severity: Severity::Warning,
+    description: "the condition of nuzzles or nyaa is always the same, nyaa (truwu) is allowed",
+};
+pub const UNCHANGING_LOOP: Rule = Rule {
+    id: "unchanging-loop",
+    severity: Severity::Warning,
+    description: "nothing in a nyaa loop changes its condition or leaves the loop",
+};
+pub const SELF_COMPARISON: Rule = Rule {
+    id: "self-comparison",
+    severity: Severity::Warning,
+    description: "a value is compared with itself",
+};
+pub const MIXED_TERMINATORS: Rule = Rule {
+    id: "mixed-terminators",
+    severity: Severity::Warning,
+    description: "statements in one file end with both ; and :3",
+};
+
+/// Every rule the linter knows about.
+#[cfg(test)]
+pub const RULES: &[&Rule] = &[
+    &UNUSED_BINDING,
+    &UNREACHABLE_CODE,
+    &CONSTANT_CONDITION,
+    &UNCHANGING_LOOP,
+    &SELF_COMPARISON,
+    &MIXED_TERMINATORS,
+];
+
+/// Something a rule found, along with where it is in the source.
+#[derive(Debug, PartialEq)]
+pub struct Lint {
+    pub rule: &'static Rule,
+    pub message: String,
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}]: {}",
+            self.rule.severity, self.rule.id, self.message
+        )
+    }
+}
+
+/// Runs every rule over a program, returning what they find in source order. Lints allowed by a comment are left out.
+///
+/// # Examples
+/// ```ignore
+/// let source = "owo x = 1;\n/* lint: allow(unused-binding) */\nowo y = 2;";
+/// let lints = lint(&parse_cst(source).unwrap(), source);
+/// assert_eq!(lints.len(), 1);
+/// assert_eq!(lints[0].to_string(), "warning[unused-binding]: x is never used");
+/// ```
+pub fn lint(tree: &SyntaxNode, source: &str) -> Vec<Lint> {
+    let nodes = tree.descendants();
+    let mut lints = vec![];
+    unused_bindings(&nodes, &mut lints);
+    unreachable_code(&nodes, &mut lints);
+    conditions(&nodes, &mut lints);
+    self_comparisons(&nodes, &mut lints);
+    mixed_terminators(tree, &mut lints);
+
+    let allowed = allowed_rules(tree, source);
+    lints.retain(|lint| {
+        let (line, _) = line_col(source, lint.span.start);
+        !allowed
+            .get(&line)
+            .is_some_and(|rules| rules.contains(lint.rule.id))
+    });
+    lints.sort_by_key(|lint| lint.span.start);
+    lints
+}
+
+fn push(lints: &mut Vec<Lint>, rule: &'static Rule, message: String, span: Range<usize>) {
+    lints.push(Lint {
+        rule,
+        message,
+        span,
+    });
+}
+
+fn name_of(token: &SyntaxToken) -> &str {
+    match &token.token {
+        Token::IDENT(name) => name,
+        _ => "",
+    }
+}
+
+/// The names read anywhere within the nodes, which includes calls and assignments to an index.
+fn used_names<'a>(nodes: &[&'a SyntaxNode]) -> HashSet<&'a str> {
+    nodes
+        .iter()
+        .filter(|node| node.kind() == SyntaxKind::Name)
+        .flat_map(|node| node.names())
+        .map(name_of)
+        .collect()
+}
+
+fn unused_bindings(nodes: &[&SyntaxNode], lints: &mut Vec<Lint>) {
+    let used = used_names(nodes);
+    for node in nodes.iter().filter(|node| node.kind() == SyntaxKind::Let) {
+        if let Some(name) = node.names().next() {
+            if !used.contains(name_of(name)) {
+                let message = format!("{} is never used", name_of(name));
+                push(lints, &UNUSED_BINDING, message, name.span.clone());
+            }
+        }
+    }
+}
+
+fn unreachable_code(nodes: &[&SyntaxNode], lints: &mut Vec<Lint>) {
+    for node in nodes {
+        if !matches!(node.kind(), SyntaxKind::Program | SyntaxKind::Block) {
+            continue;
+        }
+        let mut statements = node.nodes();
+        let exit = statements.by_ref().find(|statement| {
+            matches!(
+                statement.kind(),
+                SyntaxKind::Return | SyntaxKind::Break | SyntaxKind::Continue
+            )
+        });
+        if let (Some(exit), Some(next)) = (exit, statements.next()) {
+            let keyword = exit.tokens()[0].text.clone();
+            let message = format!("this statement can never run as it comes after {}", keyword);
+            push(lints, &UNREACHABLE_CODE, message, next.span());
+        }
+    }
+}
+
+/// Conditions made only of literals give the same result every time.
+fn is_constant(condition: &SyntaxNode) -> bool {
+    !condition
+        .descendants()
+        .iter()
+        .any(|node| matches!(node.kind(), SyntaxKind::Name | SyntaxKind::Call))
+}
+
+fn conditions(nodes: &[&SyntaxNode], lints: &mut Vec<Lint>) {
+    for node in nodes {
+        let keyword = match node.kind() {
+            SyntaxKind::If => "nuzzles",
+            SyntaxKind::While => "nyaa",
+            _ => continue,
+        };
+        let mut parts = node.nodes();
+        let (condition, body) = match (parts.next(), parts.next()) {
+            (Some(condition), Some(body)) => (condition, body),
+            _ => continue,
+        };
+
+        if is_constant(condition) {
+            // nyaa (truwu) is how loops that end with bweak are written.
+            if keyword == "nyaa" && condition.to_expr() == Some(Expr::Boolean(true)) {
+                continue;
+            }
+            let message = format!("the condition of {} is always the same", keyword);
+            push(lints, &CONSTANT_CONDITION, message, condition.span());
+        } else if keyword == "nyaa" && !loop_can_change(condition, body) {
+            let message = String::from(
+                "nothing in this loop changes its condition, so it runs forever or not at all",
+            );
+            push(lints, &UNCHANGING_LOOP, message, condition.span());
+        }
+    }
+}
+
+/// Checks whether a loop body might change what its condition reads, or leave the loop some other way. Calls in the condition could return anything.
+///
+/// # Technical Information
+/// Functions run in a copy of the caller's scope, so only definitions written in the body itself can change the variables the condition reads.
+fn loop_can_change(condition: &SyntaxNode, body: &SyntaxNode) -> bool {
+    let condition_nodes = condition.descendants();
+    // A call may change anything, and a sugoi or bweak in the condition leaves the loop from there.
+    if condition_nodes.iter().any(|node| {
+        matches!(
+            node.kind(),
+            SyntaxKind::Call | SyntaxKind::Return | SyntaxKind::Break
+        )
+    }) {
+        return true;
+    }
+    let read = used_names(&condition_nodes);
+    body.descendants().iter().any(|node| match node.kind() {
+        SyntaxKind::Return | SyntaxKind::Break => true,
+        SyntaxKind::Call => node
+            .nodes()
+            .next()
+            .and_then(|callee| callee.names().next())
+            .is_some_and(|name| name_of(name) == "quwuit"),
+        SyntaxKind::Let | SyntaxKind::Define => node
+            .names()
+            .next()
+            .is_some_and(|name| read.contains(name_of(name))),
+        SyntaxKind::IndexAssign => node.nodes().next().is_some_and(|target| {
+            used_names(&target.descendants())
+                .iter()
+                .any(|name| read.contains(name))
+        }),
+        _ => false,
+    })
+}
+
+fn self_comparisons(nodes: &[&SyntaxNode], lints: &mut Vec<Lint>) {
+    for node in nodes.iter().filter(|node| node.kind() == SyntaxKind::Infix) {
+        let operator = node.children().iter().find_map(|child| match child {
+            SyntaxElement::Token(token) => Some(&token.token),
+            SyntaxElement::Node(_) => None,
+        });
+        let is_comparison = matches!(
+            operator,
+            Some(Token::EQ | Token::NEQ | Token::LE | Token::LEQ | Token::GR | Token::GEQ)
+        );
+        // Calls may give a different value each time.
+        let has_call = node
+            .descendants()
+            .iter()
+            .any(|node| node.kind() == SyntaxKind::Call);
+        if !is_comparison || has_call {
+            continue;
+        }
+        let mut operands = node.nodes().map(SyntaxNode::to_expr);
+        if let (Some(Some(left)), Some(Some(right))) = (operands.next(), operands.next()) {
+            if left == right {
+                let message = format!("{} is compared with itself", left);
+                push(lints, &SELF_COMPARISON, message, node.span());
+            }
+        }
+    }
+}
+
+fn mixed_terminators(tree: &SyntaxNode, lints: &mut Vec<Lint>) {
+    let tokens = tree.tokens();
+    let mut terminators = tokens
+        .iter()
+        .filter(|token| token.token == Token::SEMICOLON);
+    let first = match terminators.next() {
+        Some(first) => first,
+        None => return,
+    };
+    if let Some(other) = terminators.find(|token| token.text != first.text) {
+        let message = format!(
+            "statements end with {} here but {} elsewhere, pick one",
+            other.text, first.text
+        );
+        push(lints, &MIXED_TERMINATORS, message, other.span.clone());
+    }
+}
+
+/// Finds the rules allowed by comments, keyed by the lines they apply to. A comment covers the line it ends on and the one after.
+fn allowed_rules(tree: &SyntaxNode, source: &str) -> HashMap<usize, HashSet<String>> {
+    let mut allowed: HashMap<usize, HashSet<String>> = HashMap::new();
+    for token in tree.tokens() {
+        // Trivia sits right before its token, so offsets are found by walking back from it.
+        let mut end = token.span.start;
+        for trivia in token.leading.iter().rev() {
+            let start = end - trivia.text().len();
+            if let Trivia::Comment(text) = trivia {
+                let (line, _) = line_col(source, end);
+                for rule in parse_allow(text) {
+                    for covered in [line, line + 1].iter() {
+                        allowed.entry(*covered).or_default().insert(rule.clone());
+                    }
+                }
+            }
+            end = start;
+        }
+    }
+    allowed
+}
+
+/// Reads the rule names out of a comment such as `/* lint: allow(unused-binding, self-comparison) */`.
+fn parse_allow(comment: &str) -> Vec<String> {
+    let rest = match comment.find("lint: allow(") {
+        Some(start) => &comment[start + "lint: allow(".len()..],
+        None => return vec![],
+    };
+    let list = match rest.find(')') {
+        Some(end) => &rest[..end],
+        None => return vec![],
+    };
+    list.split(',')
+        .map(|rule| String::from(rule.trim()))
+        .filter(|rule| !rule.is_empty())
+        .collect()
+}
@@ -0,0 +1,6 @@
+//! Static checks over parsed programs, which find mistakes before any code runs.
+
+pub mod arity;
+pub mod diagnostic;
+pub mod lint;
+pub mod resolver;
@@ -0,0 +1,345 @@
+//! Works out which definition every name in a program refers to, without running it.
+//!
+//! Only function bodies open a new scope, the bodies of nuzzles and nyaa define into the scope around them and fur only owns its loop variable. This matches how the evaluator sets up its environments.
+//!
+//! Calls run in the scope of their caller, so a function may use a name that only its callers define. Such names are warned about rather than reported as undefined.
+use crate::analysis::diagnostic::Severity;
+use crate::lexer::trivia::SyntaxToken;
+use crate::parser::cst::{SyntaxKind, SyntaxNode};
+use crate::token::token::Token;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+
+/// A problem with the names used in a program.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NameProblem {
+    /// Nothing by this name is defined anywhere it could be seen from.
+    Undefined(String),
+    /// The name is defined, but only by a statement that runs after it is used.
+    UsedBeforeDefinition(String),
+    DuplicateParameter {
+        function: String,
+        parameter: String,
+    },
+    /// A definition hides a name from an enclosing scope or a builtin.
+    Shadowed(String),
+    /// The name is not defined around the function using it, but another function binds it, so it may come from the scope of a caller.
+    DefinedByCaller(String),
+}
+
+impl NameProblem {
+    pub fn severity(&self) -> Severity {
+        match self {
+            NameProblem::Shadowed(_) | NameProblem::DefinedByCaller(_) => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for NameProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NameProblem::Undefined(name) => write!(f, "{} is not defined", name),
+            NameProblem::UsedBeforeDefinition(name) => {
+                write!(f, "{} is used before it is defined", name)
+            }
+            NameProblem::DuplicateParameter {
+                function,
+                parameter,
+            } => write!(
+                f,
+                "{} has more than one parameter called {}",
+                function, parameter
+            ),
+            NameProblem::Shadowed(name) => {
+                write!(f, "{} shadows a definition from an outer scope", name)
+            }
+            NameProblem::DefinedByCaller(name) => write!(
+                f,
+                "{} is not defined around this function, so every caller has to define it",
+                name
+            ),
+        }
+    }
+}
+
+/// A problem along with the byte range of the name it is about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameDiagnostic {
+    pub problem: NameProblem,
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for NameDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.problem.fmt(f)
+    }
+}
+
+/// Checks every name used in a program, returning each problem once in the order they were found. Known names, i.e builtins, count as defined before the program starts.
+///
+/// # Technical Information
+/// Function bodies only run when called, by which point anything the enclosing scopes define may exist. Names used inside of a function therefore resolve against everything its enclosing scopes define, in any order.
+///
+/// # Examples
+/// ```ignore
+/// let tree = parse_cst("dprint(x); owo x = 1;").unwrap();
+/// let diagnostics = resolve(&tree, &[String::from("dprint")]);
+/// assert_eq!(diagnostics[0].problem, NameProblem::UsedBeforeDefinition(String::from("x")));
+/// assert_eq!(diagnostics[0].span, 7..8);
+/// ```
+pub fn resolve(tree: &SyntaxNode, known: &[String]) -> Vec<NameDiagnostic> {
+    let mut resolver = Resolver {
+        scopes: vec![],
+        known: known.iter().cloned().collect(),
+        bound_anywhere: bound_anywhere(tree),
+        diagnostics: vec![],
+    };
+    resolver.function_scope(tree, vec![]);
+    resolver.diagnostics
+}
+
+/// The names one scope can see.
+struct Scope {
+    /// Names defined by the statements run so far.
+    defined: HashSet<String>,
+    /// Every name the scope defines, including those defined later on.
+    defines: HashSet<String>,
+    /// Loop scopes hold only their variable, anything else passes through to the scope around them.
+    is_loop: bool,
+}
+
+struct Resolver {
+    scopes: Vec<Scope>,
+    known: HashSet<String>,
+    /// Every name the program binds in any scope, which a caller's scope might provide.
+    bound_anywhere: HashSet<String>,
+    diagnostics: Vec<NameDiagnostic>,
+}
+
+impl Resolver {
+    fn report(&mut self, problem: NameProblem, token: &SyntaxToken) {
+        if !self
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.problem == problem)
+        {
+            self.diagnostics.push(NameDiagnostic {
+                problem,
+                span: token.span.clone(),
+            });
+        }
+    }
+
+    /// Resolves a program or function body in a scope of its own.
+    fn function_scope(&mut self, body: &SyntaxNode, parameters: Vec<&SyntaxToken>) {
+        let mut defines = HashSet::new();
+        for statement in body.nodes() {
+            collect_definitions(statement, &mut defines);
+        }
+        let parameters: HashSet<String> = parameters.into_iter().map(name_of).collect();
+        defines.extend(parameters.iter().cloned());
+        self.scopes.push(Scope {
+            defined: parameters,
+            defines,
+            is_loop: false,
+        });
+        self.statements(body);
+        self.scopes.pop();
+    }
+
+    /// Resolves the statements of a program or block.
+    fn statements(&mut self, block: &SyntaxNode) {
+        for statement in block.nodes() {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &SyntaxNode) {
+        match statement.kind() {
+            SyntaxKind::Let => {
+                if let Some(value) = statement.nodes().next() {
+                    self.expr(value);
+                }
+                if let Some(name) = statement.names().next() {
+                    self.define(name);
+                }
+            }
+            // Functions are defined before their body is looked at, so they can call themselves.
+            SyntaxKind::Define => {
+                let mut nodes = statement.nodes();
+                if let (Some(name), Some(parameters), Some(body)) =
+                    (statement.names().next(), nodes.next(), nodes.next())
+                {
+                    self.define(name);
+                    self.function(name, parameters, body);
+                }
+            }
+            SyntaxKind::IndexAssign => {
+                let mut nodes = statement.nodes();
+                if let Some(target) = nodes.next() {
+                    let mut target = target.nodes();
+                    if let Some(name) = target.next().and_then(|name| name.names().next()) {
+                        self.use_name(name);
+                    }
+                    target.for_each(|index| self.expr(index));
+                }
+                nodes.for_each(|value| self.expr(value));
+            }
+            _ => statement.nodes().for_each(|expr| self.expr(expr)),
+        }
+    }
+
+    fn function(&mut self, name: &SyntaxToken, parameters: &SyntaxNode, body: &SyntaxNode) {
+        let mut seen = HashSet::new();
+        for parameter in parameters.names() {
+            if !seen.insert(name_of(parameter)) {
+                self.report(
+                    NameProblem::DuplicateParameter {
+                        function: name_of(name),
+                        parameter: name_of(parameter),
+                    },
+                    parameter,
+                );
+            }
+        }
+        self.function_scope(body, parameters.names().collect());
+    }
+
+    fn expr(&mut self, expr: &SyntaxNode) {
+        match expr.kind() {
+            SyntaxKind::Name => {
+                if let Some(name) = expr.names().next() {
+                    self.use_name(name);
+                }
+            }
+            // The blocks of nuzzles and nyaa define into the scope around them.
+            SyntaxKind::Block => self.statements(expr),
+            SyntaxKind::For => {
+                let mut nodes = expr.nodes();
+                let (variable, iterable, body) =
+                    match (expr.names().next(), nodes.next(), nodes.next()) {
+                        (Some(variable), Some(iterable), Some(body)) => (variable, iterable, body),
+                        _ => return,
+                    };
+                self.expr(iterable);
+                let name = name_of(variable);
+                if self.is_visible(&name) {
+                    self.report(NameProblem::Shadowed(name.clone()), variable);
+                }
+                let loop_variable: HashSet<String> = vec![name].into_iter().collect();
+                self.scopes.push(Scope {
+                    defined: loop_variable.clone(),
+                    defines: loop_variable,
+                    is_loop: true,
+                });
+                self.statements(body);
+                self.scopes.pop();
+            }
+            _ => expr.nodes().for_each(|child| self.expr(child)),
+        }
+    }
+
+    /// Records a definition in the innermost scope that owns it, warning if it hides a name from further out.
+    fn define(&mut self, token: &SyntaxToken) {
+        let name = name_of(token);
+        let index = self
+            .scopes
+            .iter()
+            .rposition(|scope| !scope.is_loop || scope.defines.contains(&name))
+            .expect("the program has a scope of its own");
+        let scope = &self.scopes[index];
+        if !scope.defined.contains(&name)
+            && (self.known.contains(&name)
+                || self.scopes[..index]
+                    .iter()
+                    .any(|outer| outer.defines.contains(&name)))
+        {
+            self.report(NameProblem::Shadowed(name.clone()), token);
+        }
+        self.scopes[index].defined.insert(name);
+    }
+
+    fn is_visible(&self, name: &str) -> bool {
+        self.known.contains(name) || self.scopes.iter().any(|scope| scope.defined.contains(name))
+    }
+
+    /// Checks that a name is defined by the time it is used.
+    fn use_name(&mut self, token: &SyntaxToken) {
+        let name = name_of(token);
+        // Crossing into an enclosing function's scope means the use is deferred until a call.
+        let mut deferred = false;
+        for scope in self.scopes.iter().rev() {
+            if scope.defined.contains(&name) || (deferred && scope.defines.contains(&name)) {
+                return;
+            }
+            if scope.defines.contains(&name) {
+                self.report(NameProblem::UsedBeforeDefinition(name), token);
+                return;
+            }
+            deferred |= !scope.is_loop;
+        }
+        if self.known.contains(&name) {
+            return;
+        }
+        // Calls run in the scope of their caller, so a name bound by some other function may be there by the time this one runs.
+        let in_function = self.scopes.iter().filter(|scope| !scope.is_loop).count() > 1;
+        if in_function && self.bound_anywhere.contains(&name) {
+            self.report(NameProblem::DefinedByCaller(name), token);
+        } else {
+            self.report(NameProblem::Undefined(name), token);
+        }
+    }
+}
+
+/// The name held by an identifier token.
+fn name_of(token: &SyntaxToken) -> String {
+    match &token.token {
+        Token::IDENT(name) => name.clone(),
+        token => format!("{:?}", token),
+    }
+}
+
+/// Gathers the names a statement defines into its own scope, i.e not counting the bodies of functions. Only the blocks of nuzzles, nyaa and fur can define anything within an expression, though they may be nested inside of others.
+fn collect_definitions(node: &SyntaxNode, names: &mut HashSet<String>) {
+    match node.kind() {
+        SyntaxKind::Let => names.extend(node.names().map(name_of)),
+        SyntaxKind::Define => {
+            names.extend(node.names().map(name_of));
+            return;
+        }
+        SyntaxKind::For => {
+            let mut nodes = node.nodes();
+            if let Some(iterable) = nodes.next() {
+                collect_definitions(iterable, names);
+            }
+            // The loop variable stays inside of the loop.
+            let mut inner = HashSet::new();
+            nodes.for_each(|body| collect_definitions(body, &mut inner));
+            for variable in node.names() {
+                inner.remove(&name_of(variable));
+            }
+            names.extend(inner);
+            return;
+        }
+        _ => (),
+    }
+    for child in node.nodes() {
+        collect_definitions(child, names);
+    }
+}
+
+/// Gathers every name bound anywhere in the program, by a definition, a parameter or a loop.
+fn bound_anywhere(tree: &SyntaxNode) -> HashSet<String> {
+    tree.descendants()
+        .iter()
+        .filter(|node| {
+            matches!(
+                node.kind(),
+                SyntaxKind::Let | SyntaxKind::Define | SyntaxKind::ParameterList | SyntaxKind::For
+            )
+        })
+        .flat_map(|node| node.names().map(name_of))
+        .collect()
+}
@@ -0,0 +1,399 @@
+//! Subcommands of the uwucode binary and the exit codes they return.
+use crate::analysis::arity::check_arity;
+use crate::analysis::diagnostic::{line_col, Severity};
+use crate::analysis::lint::lint;
+use crate::analysis::resolver::resolve;
+use crate::eval::eval::{Object, GOODBYE};
+use crate::eval::native::NativeRegistry;
+use crate::formatter::formatter::{first_changed_line, format_source, FormatOptions, Terminator};
+use crate::interpreter::error::Error;
+use crate::interpreter::interpreter::{Backend, Interpreter};
+use crate::lexer::lexer::Lexer;
+use crate::parser::cst::parse_cst;
+use crate::parser::parser::parse;
+use crate::repl::repl;
+use crate::vm::bytecode::Program;
+use crate::vm::compiler::compile_source;
+use crate::vm::disasm::disassemble;
+use crate::vm::uwuc::{decode, encode, is_compiled};
+use clap::{Parser, Subcommand};
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// The program ran to completion.
+pub const EXIT_SUCCESS: i32 = 0;
+/// fmt --check found a file that is not formatted.
+pub const EXIT_UNFORMATTED: i32 = 1;
+/// lint found a problem from a rule whose severity is error.
+pub const EXIT_LINT_ERROR: i32 = 1;
+/// A script could not be read.
+pub const EXIT_IO_ERROR: i32 = 66;
+/// A script could not be parsed or loaded, or check found errors in it.
+pub const EXIT_PARSE_ERROR: i32 = 65;
+/// A script raised an error while running.
+pub const EXIT_RUNTIME_ERROR: i32 = 70;
+
+const EXIT_CODES: &str = "Exit codes:
+  0    success
+  1    fmt --check found unformatted files, or lint found errors
+  2    invalid command line arguments
+  65   the script could not be parsed or loaded, or check found errors in it
+  66   the script could not be read
+  70   the script raised a runtime error
+  n    the script called quwuit(n), quwuit() exits with 69";
+
+/// uwucode, a language based around egirl slang.
+#[derive(Parser)]
+#[command(name = "uwucode", version, after_help = EXIT_CODES)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs a script, use - to read it from standard input. Scripts compiled with compile always run on the VM.
+    #[command(alias = "open")]
+    Run {
+        file: String,
+        /// Arguments passed on to the script.
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+        /// Runs the syntax tree directly with tree, or compiles it to bytecode first with vm.
+        #[arg(long, default_value = "tree")]
+        backend: Backend,
+    },
+    /// Opens a REPL to evaluate uwucode.
+    Repl,
+    /// Evaluates code given on the command line and prints its value.
+    Eval {
+        #[arg(short = 'e', long = "expr")]
+        code: String,
+        /// Runs the syntax tree directly with tree, or compiles it to bytecode first with vm.
+        #[arg(long, default_value = "tree")]
+        backend: Backend,
+    },
+    /// Compiles a script to bytecode so it can be run later without parsing it again.
+    Compile {
+        file: String,
+        /// Where to write the compiled program, use - for standard output. Defaults to the script's path ending in .uwuc.
+        #[arg(short = 'o', long)]
+        output: Option<String>,
+        /// Leaves out the line table, which is only used to show where instructions came from.
+        #[arg(long)]
+        strip: bool,
+    },
+    /// Prints the bytecode of a script, or of a compiled program.
+    Disasm { file: String },
+    /// Prints the tokens of a script.
+    Lex { file: String },
+    /// Prints the syntax tree of a script.
+    Parse { file: String },
+    /// Checks that a script parses, only uses names it defines and calls functions with the right number of arguments, without running it.
+    Check { file: String },
+    /// Points out code in scripts that probably doesn't do what was meant. Rules can be allowed with a comment such as /* lint: allow(unused-binding) */ on or before the line.
+    Lint {
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
+    /// Formats scripts in place, use - to format standard input to standard output.
+    Fmt {
+        #[arg(required = true)]
+        files: Vec<String>,
+        /// Lists unformatted files instead of rewriting them.
+        #[arg(long)]
+        check: bool,
+        /// Ends statements with ; or :3.
+        #[arg(long, default_value = ";")]
+        terminator: Terminator,
+    },
+}
+
+/// Parses the command line and runs the subcommand, returning the exit code. Invalid arguments print a usage message and exit from here.
+pub fn run<I, T>(args: I) -> i32
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    match Cli::parse_from(args).command {
+        Command::Run {
+            file,
+            args,
+            backend,
+        } => match read_script(&file) {
+            Ok(script) => run_script(&file, script, args, backend, false),
+            Err(error) => report(&file, error),
+        },
+        Command::Repl => repl::start(),
+        Command::Eval { code, backend } => {
+            run_script("uwucode", Script::Source(code), vec![], backend, true)
+        }
+        Command::Compile {
+            file,
+            output,
+            strip,
+        } => {
+            let output = output.unwrap_or_else(|| match file.as_str() {
+                "-" => String::from("-"),
+                file => Path::new(file)
+                    .with_extension("uwuc")
+                    .to_string_lossy()
+                    .into_owned(),
+            });
+            compile_file(&file, &output, strip)
+        }
+        Command::Disasm { file } => {
+            let program = read_script(&file).and_then(|script| match script {
+                Script::Source(source) => Ok(compile_source(&source)?),
+                Script::Compiled(program) => Ok(program),
+            });
+            match program {
+                Ok(program) => {
+                    print!("{}", disassemble(&program));
+                    EXIT_SUCCESS
+                }
+                Err(error) => report(&file, error),
+            }
+        }
+        Command::Lex { file } => with_source(&file, |source| {
+            // The lexer returns its tokens in reverse so the parser can pop them.
+            for token in Lexer::new(source).lex().iter().rev() {
+                println!("{:?}", token);
+            }
+            Ok(())
+        }),
+        Command::Parse { file } => with_source(&file, |source| {
+            for statement in parse(&mut Lexer::new(source).lex())? {
+                println!("{:#?}", statement);
+            }
+            Ok(())
+        }),
+        Command::Check { file } => match read_source(&file) {
+            Ok(source) => check_source(&file, &source),
+            Err(error) => report(&file, error),
+        },
+        Command::Lint { files } => files
+            .iter()
+            .map(|file| match read_source(file) {
+                Ok(source) => lint_source(file, &source),
+                Err(error) => report(file, error),
+            })
+            .max()
+            .unwrap_or(EXIT_SUCCESS),
+        Command::Fmt {
+            files,
+            check,
+            terminator,
+        } => {
+            let options = FormatOptions { terminator };
+            // Every file is looked at, the exit code is the most serious problem found.
+            files
+                .iter()
+                .map(|file| format_file(file, check, &options))
+                .max()
+                .unwrap_or(EXIT_SUCCESS)
+        }
+    }
+}
+
+/// Runs the static checks on a script, printing what they find. Only errors fail the check, warnings are just shown.
+fn check_source(file: &str, source: &str) -> i32 {
+    let tree = match parse_cst(source) {
+        Ok(tree) => tree,
+        Err(error) => return report(file, error.into()),
+    };
+    let natives = NativeRegistry::with_system(vec![]);
+    let known: Vec<String> = natives.names().cloned().collect();
+    let location = |offset| {
+        let (line, column) = line_col(source, offset);
+        format!("{}:{}:{}", file, line, column)
+    };
+    let mut code = EXIT_SUCCESS;
+    for diagnostic in resolve(&tree, &known) {
+        let severity = diagnostic.problem.severity();
+        eprintln!(
+            "{}: {}: {}",
+            location(diagnostic.span.start),
+            severity,
+            diagnostic
+        );
+        if severity == Severity::Error {
+            code = EXIT_PARSE_ERROR;
+        }
+    }
+    for mismatch in check_arity(&tree, &natives) {
+        eprintln!("{}: error: {}", location(mismatch.call.start), mismatch);
+        match mismatch.definition {
+            Some(definition) => eprintln!(
+                "{}: note: {} is defined here",
+                location(definition.start),
+                mismatch.name
+            ),
+            None => eprintln!("note: {} is a builtin", mismatch.name),
+        }
+        code = EXIT_PARSE_ERROR;
+    }
+    code
+}
+
+/// Lints a script, printing every problem found. Only problems from rules whose severity is error fail.
+fn lint_source(file: &str, source: &str) -> i32 {
+    let tree = match parse_cst(source) {
+        Ok(tree) => tree,
+        Err(error) => return report(file, error.into()),
+    };
+    let mut code = EXIT_SUCCESS;
+    for lint in lint(&tree, source) {
+        let (line, column) = line_col(source, lint.span.start);
+        println!("{}:{}:{}: {}", file, line, column, lint);
+        if lint.rule.severity == Severity::Error {
+            code = EXIT_LINT_ERROR;
+        }
+    }
+    code
+}
+
+/// Formats one file, rewriting it only if it changed. With --check the file is left alone and reported instead.
+fn format_file(file: &str, check: bool, options: &FormatOptions) -> i32 {
+    let result = read_source(file).and_then(|source| {
+        let formatted = format_source(&source, options)?;
+        Ok((first_changed_line(&source, &formatted), formatted))
+    });
+    match result {
+        Ok((Some(line), _)) if check => {
+            println!("{}:{}: not formatted", file, line);
+            EXIT_UNFORMATTED
+        }
+        Ok((_, formatted)) if file == "-" && !check => {
+            print!("{}", formatted);
+            EXIT_SUCCESS
+        }
+        Ok((Some(_), formatted)) if !check => match fs::write(file, formatted) {
+            Ok(()) => EXIT_SUCCESS,
+            Err(error) => report(file, error.into()),
+        },
+        Ok(_) => EXIT_SUCCESS,
+        Err(error) => report(file, error),
+    }
+}
+
+/// Reads a script from a file, or from standard input if the path is -.
+fn read_source(file: &str) -> Result<String, Error> {
+    if file == "-" {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source)?;
+        Ok(source)
+    } else {
+        Ok(fs::read_to_string(file)?)
+    }
+}
+
+/// A script as run was given it, either source or a program compiled ahead of time.
+enum Script {
+    Source(String),
+    Compiled(Program),
+}
+
+/// Reads a script like read_source, telling compiled programs apart by their header rather than their extension.
+fn read_script(file: &str) -> Result<Script, Error> {
+    let bytes = if file == "-" {
+        let mut bytes = vec![];
+        io::stdin().read_to_end(&mut bytes)?;
+        bytes
+    } else {
+        fs::read(file)?
+    };
+    if is_compiled(&bytes) {
+        return Ok(Script::Compiled(decode(&bytes)?));
+    }
+    match String::from_utf8(bytes) {
+        Ok(source) => Ok(Script::Source(source)),
+        Err(error) => Err(io::Error::new(io::ErrorKind::InvalidData, error).into()),
+    }
+}
+
+/// Compiles a script and writes the program out, to standard output if the path is -.
+fn compile_file(file: &str, output: &str, strip: bool) -> i32 {
+    let mut program = match read_source(file).and_then(|source| Ok(compile_source(&source)?)) {
+        Ok(program) => program,
+        Err(error) => return report(file, error),
+    };
+    if strip {
+        program.strip_lines();
+    }
+    let bytes = encode(&program);
+    let written = match output {
+        "-" => io::stdout().write_all(&bytes),
+        output => fs::write(output, bytes),
+    };
+    match written {
+        Ok(()) => EXIT_SUCCESS,
+        Err(error) => report(output, error.into()),
+    }
+}
+
+/// Reads a script and hands it to a subcommand that does not run it.
+fn with_source<F>(file: &str, action: F) -> i32
+where
+    F: FnOnce(&str) -> Result<(), Error>,
+{
+    match read_source(file).and_then(|source| action(&source)) {
+        Ok(()) => EXIT_SUCCESS,
+        Err(error) => report(file, error),
+    }
+}
+
+/// Runs a whole program with access to its arguments and environment. Printing happens as the statements are evaluated, so the value is only shown if asked for. Errors are reported against origin.
+fn run_script(
+    origin: &str,
+    script: Script,
+    script_args: Vec<String>,
+    backend: Backend,
+    show_value: bool,
+) -> i32 {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_backend(backend);
+    interpreter
+        .context()
+        .set_natives(NativeRegistry::with_system(script_args));
+    let result = match script {
+        Script::Source(source) => interpreter.eval_str(&source),
+        Script::Compiled(program) => interpreter.run_program(program),
+    };
+    let shown = match result {
+        Ok(value) if show_value && value != Object::Null => interpreter
+            .context()
+            .output()
+            .write_line(&value.to_string())
+            .map_err(Error::from),
+        Ok(_) => Ok(()),
+        Err(error) => Err(error),
+    };
+    match shown {
+        Ok(()) => EXIT_SUCCESS,
+        Err(Error::Terminated(code)) => {
+            // The program chose its exit code, which a goodbye that can't be written shouldn't change.
+            let _ = interpreter.context().output().write_line(GOODBYE);
+            code
+        }
+        Err(error) => report(origin, error),
+    }
+}
+
+/// Prints an error to standard error and picks the exit code for it.
+pub fn report(origin: &str, error: Error) -> i32 {
+    eprintln!("{}: {}", origin, error);
+    exit_code(&error)
+}
+
+/// Maps an error onto the exit code documented in --help.
+pub fn exit_code(error: &Error) -> i32 {
+    match error {
+        Error::Io(_) => EXIT_IO_ERROR,
+        Error::Parse(_) | Error::Load(_) => EXIT_PARSE_ERROR,
+        Error::Runtime(_) => EXIT_RUNTIME_ERROR,
+        Error::Terminated(code) => *code,
+    }
+}
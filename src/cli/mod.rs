@@ -0,0 +1,3 @@
+//! The CLI module parses command line arguments and runs the requested subcommand.
+
+pub mod cli;
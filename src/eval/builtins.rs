@@ -0,0 +1,197 @@
+//! The standard builtins, registered as native functions.
+use crate::eval::context::Context;
+use crate::eval::error::RuntimeError;
+use crate::eval::eval::{ControlFlow, MapKey, Object, DEFAULT_EXIT_CODE};
+use crate::eval::native::{Arity, NativeRegistry};
+use std::env;
+
+/// Registers every builtin.
+///
+/// # Current Builtins
+/// - len (prints the length of strings)
+/// - quwuit (terminates the program, optionally with an exit code)
+/// - dprint (prints a statement, as a side effect)
+/// - nya_input (reads a line of input, optionally printing a prompt first)
+/// - keys (returns the keys of a map as an array)
+/// - values (returns the values of a map as an array)
+/// - has (checks whether a map contains a key)
+/// - remove (returns a copy of a map without a key)
+/// - range (returns an array of integers from start to end by step)
+pub fn register(registry: &mut NativeRegistry) {
+    registry.register("len", 1, len);
+    registry.register_intrinsic("quwuit", Arity::Between(0, 1), quwuit);
+    registry.register_intrinsic("dprint", 1, dprint);
+    registry.register_intrinsic("nya_input", Arity::Between(0, 1), nya_input);
+    registry.register("keys", 1, keys);
+    registry.register("values", 1, values);
+    registry.register("has", 2, has);
+    registry.register("remove", 2, remove);
+    registry.register("range", Arity::Between(1, 3), range);
+}
+
+/// Registers the builtins that expose the host system. These are left out of the standard builtins so embedders opt in to them.
+///
+/// # System Builtins
+/// - args (returns the arguments passed to the script as an array of strings)
+/// - env_var (returns an environment variable, or none if it is not set)
+pub fn register_system(registry: &mut NativeRegistry, script_args: Vec<String>) {
+    let script_args: Vec<Object> = script_args.into_iter().map(Object::String).collect();
+    registry.register("args", 0, move |_| Ok(Object::Array(script_args.clone())));
+    registry.register("env_var", 1, env_var);
+}
+
+/// BUILTIN - len
+fn len(args: &[Object]) -> Result<Object, RuntimeError> {
+    match args {
+        [Object::String(string)] => Ok(Object::Integer(string.len() as i64)),
+        [Object::Array(elements)] => Ok(Object::Integer(elements.len() as i64)),
+        [Object::Map(map)] => Ok(Object::Integer(map.len() as i64)),
+        _ => Err(RuntimeError::TypeMismatch(String::from(
+            "Non measurable type given",
+        ))),
+    }
+}
+
+/// BUILTIN - quwuit
+///
+/// Exits with the given code between 0 and 255, or 69 if none is given.
+fn quwuit(args: &[Object], _ctx: &mut Context) -> Result<Object, ControlFlow> {
+    match args {
+        [] => Err(ControlFlow::Terminate(DEFAULT_EXIT_CODE)),
+        [Object::Integer(code)] if (0..=255).contains(code) => {
+            Err(ControlFlow::Terminate(*code as i32))
+        }
+        [Object::Integer(code)] => {
+            Err(RuntimeError::Custom(format!("exit code {} is out of range", code)).into())
+        }
+        _ => Err(
+            RuntimeError::TypeMismatch(String::from("quwuit expects an integer exit code")).into(),
+        ),
+    }
+}
+
+/// BUILTIN - dprint
+fn dprint(args: &[Object], ctx: &mut Context) -> Result<Object, ControlFlow> {
+    ctx.output().write_line(&args[0].to_string())?;
+    Ok(Object::Null)
+}
+
+/// BUILTIN - nya_input
+///
+/// Returns the line as a string, or none once there is nothing left to read.
+fn nya_input(args: &[Object], ctx: &mut Context) -> Result<Object, ControlFlow> {
+    if let [prompt] = args {
+        ctx.output().write(&prompt.to_string())?;
+    }
+    Ok(match ctx.input().read_line()? {
+        Some(line) => Object::String(line),
+        None => Object::Null,
+    })
+}
+
+/// BUILTIN - keys
+fn keys(args: &[Object]) -> Result<Object, RuntimeError> {
+    match args {
+        [Object::Map(map)] => Ok(Object::Array(map.keys().map(MapKey::to_object).collect())),
+        _ => Err(RuntimeError::TypeMismatch(String::from(
+            "keys expects a single map",
+        ))),
+    }
+}
+
+/// BUILTIN - values
+fn values(args: &[Object]) -> Result<Object, RuntimeError> {
+    match args {
+        [Object::Map(map)] => Ok(Object::Array(map.values().cloned().collect())),
+        _ => Err(RuntimeError::TypeMismatch(String::from(
+            "values expects a single map",
+        ))),
+    }
+}
+
+/// BUILTIN - has
+fn has(args: &[Object]) -> Result<Object, RuntimeError> {
+    match args {
+        [Object::Map(map), key] => Ok(Object::Boolean(match MapKey::from_object(key) {
+            Some(key) => map.contains_key(&key),
+            None => false,
+        })),
+        _ => Err(RuntimeError::TypeMismatch(String::from(
+            "has expects a map and a key",
+        ))),
+    }
+}
+
+/// BUILTIN - env_var
+fn env_var(args: &[Object]) -> Result<Object, RuntimeError> {
+    match args {
+        [Object::String(name)] => Ok(match env::var(name) {
+            Ok(value) => Object::String(value),
+            Err(_) => Object::Null,
+        }),
+        _ => Err(RuntimeError::TypeMismatch(String::from(
+            "env_var expects the name of a variable",
+        ))),
+    }
+}
+
+/// BUILTIN - remove
+///
+/// Objects are values in uwucode, so the map is not modified in place. Instead a copy without the key is returned, i.e `owo m = remove(m, "key");`.
+fn remove(args: &[Object]) -> Result<Object, RuntimeError> {
+    match args {
+        [Object::Map(map), key] => {
+            let mut map = map.clone();
+            if let Some(key) = MapKey::from_object(key) {
+                map.remove(&key);
+            }
+            Ok(Object::Map(map))
+        }
+        _ => Err(RuntimeError::TypeMismatch(String::from(
+            "remove expects a map and a key",
+        ))),
+    }
+}
+
+/// The most items range will build, since the whole array is made before anything can use it.
+pub const MAX_RANGE_LENGTH: usize = 1_000_000;
+
+/// BUILTIN - range
+///
+/// Accepts range(end), range(start, end) or range(start, end, step), where end is exclusive. Ranges longer than MAX_RANGE_LENGTH are an error.
+fn range(args: &[Object]) -> Result<Object, RuntimeError> {
+    let (start, end, step) = match args {
+        [Object::Integer(end)] => (0, *end, 1),
+        [Object::Integer(start), Object::Integer(end)] => (*start, *end, 1),
+        [Object::Integer(start), Object::Integer(end), Object::Integer(step)] => {
+            (*start, *end, *step)
+        }
+        _ => {
+            return Err(RuntimeError::TypeMismatch(String::from(
+                "range expects one to three integers",
+            )))
+        }
+    };
+    if step == 0 {
+        return Err(RuntimeError::Custom(String::from(
+            "range step cannot be zero",
+        )));
+    }
+
+    // Worked out in i128 so no start, end and step can overflow.
+    let (start, end, step) = (start as i128, end as i128, step as i128);
+    let length = match (end - start + step - step.signum()) / step {
+        length if length > 0 => length,
+        _ => 0,
+    };
+    if length > MAX_RANGE_LENGTH as i128 {
+        return Err(RuntimeError::Custom(format!(
+            "range would have {} items, more than the limit of {}",
+            length, MAX_RANGE_LENGTH
+        )));
+    }
+    let elements = (0..length)
+        .map(|position| Object::Integer((start + position * step) as i64))
+        .collect();
+    Ok(Object::Array(elements))
+}
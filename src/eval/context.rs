@@ -0,0 +1,100 @@
+//! Holds the side effects a program has access to while it is evaluated.
+use crate::eval::error::RuntimeError;
+use crate::eval::input::{Input, StdInput};
+use crate::eval::native::NativeRegistry;
+use crate::eval::output::{Output, StdOutput};
+
+/// How deeply functions may be nested unless the host allows more.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+/// The context is passed alongside the environment. Unlike the environment it is not scoped, so every function frame shares it.
+pub struct Context {
+    output: Box<dyn Output>,
+    input: Box<dyn Input>,
+    natives: NativeRegistry,
+    max_call_depth: usize,
+    call_depth: usize,
+}
+
+impl Context {
+    /// Instantiates a context that prints to standard output and reads from standard input.
+    pub fn new() -> Self {
+        Self::with_output(Box::new(StdOutput))
+    }
+
+    /// Instantiates a context that prints to the given output and reads from standard input.
+    pub fn with_output(output: Box<dyn Output>) -> Self {
+        Self::with_io(output, Box::new(StdInput))
+    }
+
+    /// Instantiates a context that prints to the given output and reads from the given input. The standard builtins are registered, and the host may register more before running.
+    pub fn with_io(output: Box<dyn Output>, input: Box<dyn Input>) -> Self {
+        Context {
+            output,
+            input,
+            natives: NativeRegistry::with_builtins(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            call_depth: 0,
+        }
+    }
+
+    /// Gives access to where the program prints.
+    pub fn output(&mut self) -> &mut dyn Output {
+        self.output.as_mut()
+    }
+
+    /// Gives access to where the program reads its input from.
+    pub fn input(&mut self) -> &mut dyn Input {
+        self.input.as_mut()
+    }
+
+    /// Gives access to the natives a program can call.
+    pub fn natives(&self) -> &NativeRegistry {
+        &self.natives
+    }
+
+    /// Gives access to the natives so the host can register or remove some.
+    pub fn natives_mut(&mut self) -> &mut NativeRegistry {
+        &mut self.natives
+    }
+
+    /// Replaces every native a program can call, for instance with `NativeRegistry::with_system`.
+    pub fn set_natives(&mut self, natives: NativeRegistry) {
+        self.natives = natives;
+    }
+
+    /// How deeply functions may be nested.
+    pub fn max_call_depth(&self) -> usize {
+        self.max_call_depth
+    }
+
+    /// Calls nested deeper than this raise an error rather than overflowing the stack. The host must run the interpreter on a stack big enough for it.
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    /// Records that a function is being entered, failing if that nests calls too deeply.
+    pub(crate) fn enter_call(&mut self) -> Result<(), RuntimeError> {
+        if self.call_depth >= self.max_call_depth {
+            return Err(RuntimeError::CallDepthExceeded(self.max_call_depth));
+        }
+        self.call_depth += 1;
+        Ok(())
+    }
+
+    /// Records that a function entered with enter_call has finished.
+    pub(crate) fn leave_call(&mut self) {
+        self.call_depth -= 1;
+    }
+
+    /// Forgets calls left unfinished by a program that was stopped.
+    pub(crate) fn reset_call_depth(&mut self) {
+        self.call_depth = 0;
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
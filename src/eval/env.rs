@@ -3,24 +3,29 @@ use crate::eval::eval::Object;
 use std::collections::HashMap;
 
 /// Scopes contain bound variables and expressions.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct Env {
-    pub space: HashMap<String, Object>,
-    pub enclosing: Option<Box<Env>>, // Pointer to a scope or nothing.
+    space: HashMap<String, Object>,
+    enclosing: Option<Box<Env>>, // Pointer to a scope or nothing.
+    is_loop: bool,               // Loop scopes only own their loop variable.
 }
 
 impl Env {
     /// Instantiates a new environment object with no outer scope.
     pub fn new() -> Self {
-        Env {
-            space: HashMap::new(),
-            enclosing: None,
-        }
+        Self::default()
     }
 
-    /// Defines a variable/function in the current scope.
+    /// Defines a variable/function in the current scope. Loop scopes pass definitions through to the enclosing scope so loop bodies can update outer variables.
     pub fn set(&mut self, key: String, value: Object) {
-        self.space.insert(key, value);
+        match &mut self.enclosing {
+            Some(enclose) if self.is_loop && !self.space.contains_key(&key) => {
+                enclose.set(key, value)
+            }
+            _ => {
+                self.space.insert(key, value);
+            }
+        }
     }
 
     /// Recursively attempts to get a variable/functions value.
@@ -32,10 +37,40 @@ impl Env {
         }
     }
 
+    /// Lists the bindings made in this scope, leaving out those of the scopes around it.
+    pub fn bindings(&self) -> impl Iterator<Item = (&String, &Object)> {
+        self.space.iter()
+    }
+
+    /// Updates an existing variable in the innermost scope that defines it, returning false if it is not defined.
+    pub fn assign(&mut self, key: &str, value: Object) -> bool {
+        if let Some(slot) = self.space.get_mut(key) {
+            *slot = value;
+            return true;
+        }
+        match &mut self.enclosing {
+            Some(enclose) => enclose.assign(key, value),
+            None => false,
+        }
+    }
+
     // Enclosing = outer or global typically.
-    pub fn new_enclosing(outer: Self) -> Self {
+    pub(crate) fn new_enclosing(outer: Self) -> Self {
         let mut env = Self::new();
         env.enclosing = Some(Box::new(outer)); // Pointer to original object
         env // Return the inner environment
     }
+
+    /// Creates a scope for the body of a for loop which holds the loop variable.
+    pub(crate) fn new_loop(outer: Self, variable: String) -> Self {
+        let mut env = Self::new_enclosing(outer);
+        env.is_loop = true;
+        env.space.insert(variable, Object::Null);
+        env
+    }
+
+    /// Consumes an inner scope, returning the scope that encloses it.
+    pub(crate) fn into_enclosing(self) -> Option<Self> {
+        self.enclosing.map(|enclose| *enclose)
+    }
 }
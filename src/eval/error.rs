@@ -0,0 +1,65 @@
+//! Errors that stop a program while it is being evaluated.
+use crate::eval::native::Arity;
+use std::error;
+use std::fmt;
+
+/// Runtime errors are raised by the evaluator or by native functions, and unwind to the interpreter like any other control flow.
+#[derive(Debug, PartialEq, Clone)]
+pub enum RuntimeError {
+    /// A variable or function was used without being defined.
+    UndefinedVariable(String),
+    /// An operation was given objects of a type it does not support.
+    TypeMismatch(String),
+    /// A function was called with the wrong number of arguments.
+    WrongArity {
+        name: String,
+        expected: Arity,
+        found: usize,
+    },
+    /// An array was indexed outside of its bounds.
+    IndexOutOfRange {
+        index: i64,
+        length: usize,
+    },
+    /// A map was indexed by a key it does not contain.
+    KeyNotFound(String),
+    DivisionByZero,
+    /// An arithmetic operation gave a result that does not fit in an integer.
+    IntegerOverflow,
+    /// Functions were nested deeper than the context allows, carrying the limit.
+    CallDepthExceeded(usize),
+    /// Raised by native functions to report their own errors.
+    Custom(String),
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuntimeError::UndefinedVariable(name) => write!(f, "{} is not defined", name),
+            RuntimeError::TypeMismatch(message) => message.fmt(f),
+            RuntimeError::WrongArity {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} called with incorrect number of arguments, {} arguments required instead {} were found",
+                name, expected, found
+            ),
+            RuntimeError::IndexOutOfRange { index, length } => write!(
+                f,
+                "index {} out of range for array of length {}",
+                index, length
+            ),
+            RuntimeError::KeyNotFound(key) => write!(f, "key {} not found in map", key),
+            RuntimeError::DivisionByZero => "division by zero".fmt(f),
+            RuntimeError::IntegerOverflow => "integer overflow".fmt(f),
+            RuntimeError::CallDepthExceeded(limit) => {
+                write!(f, "functions were nested more than {} calls deep", limit)
+            }
+            RuntimeError::Custom(message) => message.fmt(f),
+        }
+    }
+}
+
+impl error::Error for RuntimeError {}
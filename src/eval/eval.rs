@@ -1,9 +1,16 @@
+use crate::parser::ast::fmt_function;
 use crate::parser::ast::Expr;
 use crate::parser::ast::Operator;
 use crate::parser::ast::Prefix;
 use crate::parser::ast::Statement;
 
+pub use crate::eval::context::Context;
 pub use crate::eval::env::Env;
+pub use crate::eval::error::RuntimeError;
+use crate::eval::native::{Arity, NativeFunction};
+use crate::vm::bytecode::CompiledFunction;
+use crate::vm::vm;
+use std::collections::BTreeMap;
 use std::fmt;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -12,30 +19,73 @@ pub enum Object {
     Integer(i64),
     String(String),
     Boolean(bool),
-    Return(Box<Object>),
-    Function {
-        parameters: Vec<String>,
-        body: Vec<Statement>,
-    },
-    Terminate,
-    Print(Box<Object>),
-    While {
-        condition: Box<Expr>,
-        instruction: Vec<Statement>,
-    },
+    Array(Vec<Object>),
+    Map(BTreeMap<MapKey, Object>),
+    Function(TreeFunction),
+    Native(NativeFunction),
+    /// A function compiled to bytecode, which runs on the VM wherever it is called from.
+    Compiled(CompiledFunction),
 }
 
-/// NOT IMPLEMENTED: Determines what action the interpreter must take. The while loop feature is not implemented as a result, and neither is the input function.
-#[allow(dead_code)]
+/// A function defined in uwucode, which the tree-walker runs from its syntax tree.
 #[derive(Debug, PartialEq, Clone)]
-pub enum InterpreterAction {
-    Print(Object),
-    Terminate,
-    Input(Object),
-    None(Object),
-    While(Object),
+pub struct TreeFunction {
+    pub(crate) parameters: Vec<String>,
+    pub(crate) body: Vec<Statement>,
 }
 
+/// The subset of objects that may be used as map keys. Keys are kept ordered so iterating a map is deterministic.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub enum MapKey {
+    Integer(i64),
+    String(String),
+    Boolean(bool),
+}
+
+impl MapKey {
+    /// Converts an object into a key, returning None for unhashable types.
+    pub fn from_object(obj: &Object) -> Option<MapKey> {
+        match obj {
+            Object::Integer(num) => Some(MapKey::Integer(*num)),
+            Object::String(string) => Some(MapKey::String(string.clone())),
+            Object::Boolean(val) => Some(MapKey::Boolean(*val)),
+            _ => None,
+        }
+    }
+
+    /// Converts a key back into the object it was made from.
+    pub fn to_object(&self) -> Object {
+        match self {
+            MapKey::Integer(num) => Object::Integer(*num),
+            MapKey::String(string) => Object::String(string.clone()),
+            MapKey::Boolean(val) => Object::Boolean(*val),
+        }
+    }
+}
+
+/// Determines what action the interpreter must take when evaluation is interrupted. Signals are carried in the error of a Result, so they unwind through any number of nested bodies until a function, loop or the interpreter handles them.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ControlFlow {
+    Return(Object),
+    Break,
+    Continue,
+    /// Carries the exit code the program was terminated with.
+    Terminate(i32),
+    Error(RuntimeError),
+}
+
+impl From<RuntimeError> for ControlFlow {
+    fn from(error: RuntimeError) -> Self {
+        ControlFlow::Error(error)
+    }
+}
+
+/// The exit code used when quwuit is called without one.
+pub const DEFAULT_EXIT_CODE: i32 = 69;
+
+/// The message printed when quwuit is called.
+pub const GOODBYE: &str = "Nyaaa!~, t-t-thanks fow u-using uwucode?!?1 Come *boops your nose* again *huggles tightly* soon?!! 🥺";
+
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -45,30 +95,66 @@ impl fmt::Display for Object {
                 true => "truwu".fmt(f),
                 false => "fowose".fmt(f),
             },
-            Object::Function {parameters:_,body:_} => "".fmt(f),
+            Object::Function(function) => {
+                fmt_function(f, None, &function.parameters, &function.body)
+            }
+            Object::Array(elements) => {
+                let items: Vec<String> = elements.iter().map(display_nested).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Object::Map(pairs) => {
+                let items: Vec<String> = pairs
+                    .iter()
+                    .map(|(key, value)| {
+                        format!(
+                            "{}: {}",
+                            display_nested(&key.to_object()),
+                            display_nested(value)
+                        )
+                    })
+                    .collect();
+                write!(f, "{{{}}}", items.join(", "))
+            }
+            Object::Native(native) => write!(f, "<native {}>", native.name),
+            Object::Compiled(function) => function.function().display.fmt(f),
             Object::Null => "none".fmt(f),
-            Object::Return(obj) => write!(f,"{}",obj),
-            Object::Terminate => "Nyaaa!~, t-t-thanks fow u-using uwucode?!?1 Come *boops your nose* again *huggles tightly* soon?!! 🥺".fmt(f),
-            Object::Print(obj) => write!(f,"{}",obj),
-            Object::While {condition:_,instruction:_} => "".fmt(f)
         }
     }
 }
-// Change to be an Action
+/// Displays an object inside of a collection, where strings are quoted to tell them apart from other values.
+fn display_nested(obj: &Object) -> String {
+    match obj {
+        Object::String(string) => format!("{:?}", string),
+        _ => obj.to_string(),
+    }
+}
+
 /// Evaluates most expressions recursively, with base cases being recognised primitives.
-fn eval_expr(expression: Expr, env: &mut Env) -> Object {
-    match expression {
+fn eval_expr(expression: Expr, env: &mut Env, ctx: &mut Context) -> Result<Object, ControlFlow> {
+    let result = match expression {
         // Match primitives into their objective form
         Expr::String(string) => Object::String(string),
         Expr::Integer(num) => Object::Integer(num),
         Expr::Boolean(val) => Object::Boolean(val),
 
-        /*
-        While {statement,body} => {
-            eval_expr(statement) => true,false -> break out and return blank eval
-            easy
+        // Collections evaluate each of their elements in order
+        Expr::Array(elements) => Object::Array(
+            elements
+                .into_iter()
+                .map(|element| eval_expr(element, env, ctx))
+                .collect::<Result<_, _>>()?,
+        ),
+        Expr::Map(pairs) => {
+            let mut map = BTreeMap::new();
+            for (key, value) in pairs {
+                let key = eval_expr(key, env, ctx)?;
+                map.insert(map_key(&key)?, eval_expr(value, env, ctx)?);
+            }
+            Object::Map(map)
+        }
+        Expr::Index { left, index } => {
+            eval_index(eval_expr(*left, env, ctx)?, eval_expr(*index, env, ctx)?)?
         }
-        */
 
         /*
         Input needs to call a stdin but print first. logic needs to be handled elsewhere.
@@ -78,48 +164,58 @@ fn eval_expr(expression: Expr, env: &mut Env) -> Object {
         /*
         not sure how to do elif*/
         // Call prefix notation expressions into here
-        Expr::Prefix { prefix, value } => eval_prefix(prefix, value, env),
+        Expr::Prefix { prefix, value } => eval_prefix(prefix, *value, env, ctx)?,
 
         // Call infix notation here
         Expr::Infix {
             left,
             operator,
             right,
-        } => eval_infix(left, operator, right, env),
+        } => eval_infix(*left, operator, *right, env, ctx)?,
 
         Expr::If {
             condition,
             consequence,
             alternative,
         } => {
-            if eval_expr(*condition, env) == Object::Boolean(true) {
-                eval_statements(consequence, env)
+            if eval_expr(*condition, env, ctx)? == Object::Boolean(true) {
+                eval_statements(consequence, env, ctx)?
             } else {
-                eval_statements(alternative, env)
+                eval_statements(alternative, env, ctx)?
             }
         }
-        Expr::Variable(name) => env.get(&name).expect("Failed to get variable"),
-        Expr::Function { parameters, body } => Object::Function { parameters, body },
+        // Variables not bound in any scope may still name a native function
+        Expr::Variable(name) => match env.get(&name) {
+            Some(value) => value,
+            None => match ctx.natives().get(&name) {
+                Some(native) => Object::Native(native.clone()),
+                None => return Err(RuntimeError::UndefinedVariable(name).into()),
+            },
+        },
+        Expr::Function { parameters, body } => Object::Function(TreeFunction { parameters, body }),
 
         // Control flow
         Expr::While {
             condition,
             instruction,
-        } => Object::While {
-            condition,
-            instruction,
-        },
-        Expr::Builtin {
-            function_name,
-            arguments,
         } => {
-            let mut obj_args: Vec<Object> = vec![];
-            for arg in arguments.iter() {
-                obj_args.push(eval_expr(arg.clone(), env));
+            while eval_expr(*condition.clone(), env, ctx)? == Object::Boolean(true) {
+                match eval_statements(instruction.clone(), env, ctx) {
+                    Err(ControlFlow::Break) => break,
+                    Ok(_) | Err(ControlFlow::Continue) => continue,
+                    Err(signal) => return Err(signal),
+                }
             }
-            eval_builtin(function_name, obj_args)
+            Object::Null
+        }
+        Expr::For {
+            variable,
+            iterable,
+            body,
+        } => {
+            let items = iterate(eval_expr(*iterable, env, ctx)?)?;
+            eval_for(variable, items, body, env, ctx)?
         }
-
         // Call logic requires setting up function frames
         Expr::Call {
             function,
@@ -128,242 +224,396 @@ fn eval_expr(expression: Expr, env: &mut Env) -> Object {
             // Prep args by evaluating and appending to vector
             let mut obj_args: Vec<Object> = vec![];
             for arg in arguments.iter() {
-                obj_args.push(eval_expr(arg.clone(), env));
+                obj_args.push(eval_expr(arg.clone(), env, ctx)?);
             }
 
-            let (parameters, body) = match *function {
-                // Scope here, clean up logic
-                Expr::Variable(func_name) => {
-                    match env.get(&func_name) {
-                        // Found a user-defined function
-                        Some(Object::Function { parameters, body }) => (parameters, body),
-
-                        // Try checking against inbuilt functions, not implemented
-                        None => {
-                            panic!("Function not found!");
-                            //return eval_builtin(&func_name, arguments);
-                        }
-                        _ => panic!("attempted to call non-function"),
-                    }
-                }
-                Expr::Function { parameters, body } => (parameters, body),
-
-                _ => panic!("attempted to call non-function"),
+            // Named functions report their name if called incorrectly
+            let func_name = match function.as_ref() {
+                Expr::Variable(name) => name.clone(),
+                _ => String::from("function"),
             };
 
-            // run user defined function
-            eval_function(body, parameters, obj_args, env)
+            let callee = eval_expr(*function, env, ctx)?;
+            call_object(func_name, callee, obj_args, env, ctx)?
         }
-    }
+    };
+    Ok(result)
 }
 
-/// Evaluates an entire body of statements.
-fn eval_statements(statements: Vec<Statement>, env: &mut Env) -> Object {
+/// Evaluates an entire body of statements. Control flow signals skip the rest of the body and are handled by the enclosing function or loop.
+fn eval_statements(
+    statements: Vec<Statement>,
+    env: &mut Env,
+    ctx: &mut Context,
+) -> Result<Object, ControlFlow> {
     let mut result = Object::Null;
 
     for statement in statements {
-        result = eval_statement(statement, env);
-
-        if let &Object::Return(_) = &result {
-            return result;
-        }
+        result = eval_statement(statement, env, ctx)?;
     }
-    result
+    Ok(result)
 }
 
 /// Evaluates primitive statements which are either function declarations, variable definitions or return statements.
-fn eval_statement(statement: Statement, env: &mut Env) -> Object {
-    match statement {
+fn eval_statement(
+    statement: Statement,
+    env: &mut Env,
+    ctx: &mut Context,
+) -> Result<Object, ControlFlow> {
+    let result = match statement {
         Statement::Define { func_name, func } => {
-            let value = eval_expr(func, env);
+            let value = eval_expr(func, env, ctx)?;
             env.set(func_name, value.clone());
             value
         }
 
         Statement::Let { name, value } => {
-            let value = eval_expr(value, env);
+            let value = eval_expr(value, env, ctx)?;
             env.set(name, value.clone());
             value
         }
 
-        Statement::Expression(expr) => eval_expr(expr, env),
+        Statement::IndexAssign { name, index, value } => {
+            let index = eval_expr(index, env, ctx)?;
+            let value = eval_expr(value, env, ctx)?;
+            assign_index(name, index, value, env)?
+        }
 
-        Statement::Return { value } => Object::Return(Box::new(eval_expr(value, env))),
-    }
+        Statement::Expression(expr) => eval_expr(expr, env, ctx)?,
+
+        Statement::Return { value } => {
+            return Err(ControlFlow::Return(eval_expr(value, env, ctx)?))
+        }
+        Statement::Break => return Err(ControlFlow::Break),
+        Statement::Continue => return Err(ControlFlow::Continue),
+    };
+    Ok(result)
 }
 
-/// Evaluates and unwraps return statements if found.
-pub fn eval_return(statements: Vec<Statement>, env: &mut Env) -> Object {
-    let result = eval_statements(statements, env);
+/// Replaces one element of the array or map bound to a name, returning the value assigned.
+pub(crate) fn assign_index(
+    name: String,
+    index: Object,
+    value: Object,
+    env: &mut Env,
+) -> Result<Object, RuntimeError> {
+    let updated = match env.get(&name) {
+        Some(Object::Array(mut elements)) => {
+            let position = array_position(&elements, &index)?;
+            elements[position] = value.clone();
+            Object::Array(elements)
+        }
+        Some(Object::Map(mut map)) => {
+            map.insert(map_key(&index)?, value.clone());
+            Object::Map(map)
+        }
+        Some(obj) => {
+            return Err(RuntimeError::TypeMismatch(format!(
+                "{} does not support index assignment",
+                obj
+            )))
+        }
+        None => return Err(RuntimeError::UndefinedVariable(name)),
+    };
+    env.assign(&name, updated);
+    Ok(value)
+}
 
-    match result {
-        Object::Return(ret) => *ret,
-        _ => result, // Don't unwrap and deref
+/// Evaluates and unwraps return statements if found. Any other signal, i.e termination, is passed on to the caller.
+pub fn eval_return(
+    statements: Vec<Statement>,
+    env: &mut Env,
+    ctx: &mut Context,
+) -> Result<Object, ControlFlow> {
+    match eval_statements(statements, env, ctx) {
+        Err(ControlFlow::Return(ret)) => Ok(ret),
+        result => result,
     }
 }
 
-/// Evaluates a single line.
-pub fn eval_return_single(statement: &Statement, env: &mut Env) -> Object {
-    let result = eval_statement(statement.clone(), env);
+/// Turns a collection into the sequence of objects a for loop binds to its variable.
+///
+/// Arrays yield their elements, strings their characters and maps their keys.
+pub(crate) fn iterate(collection: Object) -> Result<Vec<Object>, RuntimeError> {
+    match collection {
+        Object::Array(elements) => Ok(elements),
+        Object::String(string) => Ok(string
+            .chars()
+            .map(|chr| Object::String(chr.to_string()))
+            .collect()),
+        Object::Map(map) => Ok(map.keys().map(MapKey::to_object).collect()),
+        obj => Err(RuntimeError::TypeMismatch(format!(
+            "{} is not iterable",
+            obj
+        ))),
+    }
+}
 
-    match result {
-        Object::Return(ret) => *ret,
-        _ => result,
+/// Runs the body of a for loop once per item inside of a loop scope, restoring the outer scope afterwards.
+fn eval_for(
+    variable: String,
+    items: Vec<Object>,
+    body: Vec<Statement>,
+    env: &mut Env,
+    ctx: &mut Context,
+) -> Result<Object, ControlFlow> {
+    // The outer scope is moved into the loop scope rather than cloned, so definitions made in the body persist.
+    let outer = std::mem::replace(env, Env::new());
+    let mut loop_env = Env::new_loop(outer, variable.clone());
+    let mut result = Ok(Object::Null);
+
+    for item in items {
+        loop_env.set(variable.clone(), item);
+        match eval_statements(body.clone(), &mut loop_env, ctx) {
+            Err(ControlFlow::Break) => break,
+            Ok(_) | Err(ControlFlow::Continue) => continue,
+            Err(signal) => {
+                result = Err(signal);
+                break;
+            }
+        }
     }
+
+    *env = loop_env
+        .into_enclosing()
+        .expect("Loop scope lost its enclosing scope");
+    result
 }
 
 /// Binds any arguments passed in to the function scope created, returning the scope of the function.
-fn bind_local_vars(args: Vec<String>, parameters: Vec<Object>, env: &mut Env) -> Env {
+fn bind_local_vars(
+    func_name: String,
+    parameters: Vec<String>,
+    args: Vec<Object>,
+    env: &mut Env,
+) -> Result<Env, RuntimeError> {
     // for i in .... env set in the newest env
-    assert_eq!(parameters.len(),args.len(),
-        "Function called with incorrect number of arguments, {} arguments required instead {} were found.",parameters.len(),args.len()
-    );
+    if args.len() != parameters.len() {
+        return Err(RuntimeError::WrongArity {
+            name: func_name,
+            expected: Arity::Exact(parameters.len()),
+            found: args.len(),
+        });
+    }
 
     let mut closed_env = Env::new_enclosing(env.clone());
-    for (param, arg) in parameters.iter().zip(args.iter()) {
-        closed_env.set(arg.clone(), param.clone());
+    for (param, arg) in parameters.into_iter().zip(args) {
+        closed_env.set(param, arg);
+    }
+    Ok(closed_env)
+}
+
+/// Calls a user defined or native function with arguments that have already been evaluated.
+pub fn call_object(
+    func_name: String,
+    callee: Object,
+    args: Vec<Object>,
+    env: &mut Env,
+    ctx: &mut Context,
+) -> Result<Object, ControlFlow> {
+    match callee {
+        // run user defined function
+        Object::Function(function) => eval_function(
+            func_name,
+            function.body,
+            function.parameters,
+            args,
+            env,
+            ctx,
+        ),
+        Object::Native(native) => native.call(&args, ctx),
+        Object::Compiled(function) => vm::call(func_name, function, args, env, ctx),
+        obj => Err(
+            RuntimeError::TypeMismatch(format!("attempted to call non-function {}", obj)).into(),
+        ),
     }
-    closed_env
 }
 
 /// Sets up a function frame, binds local variables and execeutes the function.
 fn eval_function(
+    func_name: String,
     func_body: Vec<Statement>,
-    args: Vec<String>,
-    parameters: Vec<Object>,
+    parameters: Vec<String>,
+    args: Vec<Object>,
     env: &mut Env,
-) -> Object {
-    let mut func_env = bind_local_vars(args, parameters, env);
-    eval_return(func_body, &mut func_env)
+    ctx: &mut Context,
+) -> Result<Object, ControlFlow> {
+    ctx.enter_call()?;
+    let result = match bind_local_vars(func_name, parameters, args, env) {
+        Ok(mut func_env) => eval_return(func_body, &mut func_env, ctx),
+        Err(error) => Err(error.into()),
+    };
+    ctx.leave_call();
+    result
+}
+
+/// Looks up an element of an array by position or a map by key.
+pub(crate) fn eval_index(left: Object, index: Object) -> Result<Object, RuntimeError> {
+    match left {
+        Object::Array(elements) => {
+            let position = array_position(&elements, &index)?;
+            Ok(elements[position].clone())
+        }
+        Object::Map(map) => match map.get(&map_key(&index)?) {
+            Some(value) => Ok(value.clone()),
+            None => Err(RuntimeError::KeyNotFound(index.to_string())),
+        },
+        obj => Err(RuntimeError::TypeMismatch(format!(
+            "{} cannot be indexed",
+            obj
+        ))),
+    }
+}
+
+/// Converts an index object into a valid position in an array.
+fn array_position(elements: &[Object], index: &Object) -> Result<usize, RuntimeError> {
+    match index {
+        Object::Integer(num) if *num >= 0 && (*num as usize) < elements.len() => Ok(*num as usize),
+        Object::Integer(num) => Err(RuntimeError::IndexOutOfRange {
+            index: *num,
+            length: elements.len(),
+        }),
+        obj => Err(RuntimeError::TypeMismatch(format!(
+            "Arrays must be indexed by integers, found {}",
+            obj
+        ))),
+    }
+}
+
+/// Converts an object into a map key, failing for unhashable types.
+pub(crate) fn map_key(obj: &Object) -> Result<MapKey, RuntimeError> {
+    MapKey::from_object(obj)
+        .ok_or_else(|| RuntimeError::TypeMismatch(format!("{} cannot be used as a map key", obj)))
 }
 
 /// Handles unary operations such as negation or turning a number negative.
-fn eval_prefix(prefix: Prefix, value: Box<Expr>, env: &mut Env) -> Object {
-    match prefix {
+fn eval_prefix(
+    prefix: Prefix,
+    value: Expr,
+    env: &mut Env,
+    ctx: &mut Context,
+) -> Result<Object, ControlFlow> {
+    Ok(apply_prefix(prefix, eval_expr(value, env, ctx)?)?)
+}
+
+/// Applies a unary operator to a value that has already been evaluated.
+pub(crate) fn apply_prefix(prefix: Prefix, value: Object) -> Result<Object, RuntimeError> {
+    let message = match prefix {
         // Negative numbers
-        Prefix::Minus => match eval_expr(*value, env) {
-            Object::Integer(val) => Object::Integer(-val),
-            _ => panic!("non numeric type found, - does not support this operation"),
+        Prefix::Minus => match value {
+            Object::Integer(val) => return checked(val.checked_neg()),
+            _ => "non numeric type found, - does not support this operation",
         },
         // Logical negation
-        Prefix::Bang => match eval_expr(*value, env) {
-            Object::Boolean(val) => Object::Boolean(!val),
-            _ => panic!("Logical negation op performed on non boolean type"),
+        Prefix::Bang => match value {
+            Object::Boolean(val) => return Ok(Object::Boolean(!val)),
+            _ => "Logical negation op performed on non boolean type",
         },
-        //_ => panic!("Prefix type not recognized"),
-    }
+    };
+    Err(RuntimeError::TypeMismatch(String::from(message)))
 }
 
 /// Evaluates binary expressions via infix notation. This can include basic arithmetic or comparisons.
-fn eval_infix(left: Box<Expr>, operator: Operator, right: Box<Expr>, env: &mut Env) -> Object {
+fn eval_infix(
+    left: Expr,
+    operator: Operator,
+    right: Expr,
+    env: &mut Env,
+    ctx: &mut Context,
+) -> Result<Object, ControlFlow> {
+    let left = eval_expr(left, env, ctx)?;
+    let right = eval_expr(right, env, ctx)?;
+    Ok(apply_infix(left, operator, right)?)
+}
+
+/// Applies a binary operator to operands that have already been evaluated.
+pub(crate) fn apply_infix(
+    left: Object,
+    operator: Operator,
+    right: Object,
+) -> Result<Object, RuntimeError> {
     match operator {
         // Arithmetic group
         Operator::Plus
         | Operator::Minus
         | Operator::Multiply
         | Operator::Divide
-        | Operator::Modulo => eval_infix_op(left, operator, right, env),
+        | Operator::Modulo => eval_infix_op(left, operator, right),
         // Comparison group
         Operator::LessThan
         | Operator::GreaterThan
         | Operator::Equals
+        | Operator::NotEquals
         | Operator::LessThanEqual
-        | Operator::GreaterThanEqual => eval_infix_comp(left, operator, right, env),
-        _ => panic!("Operator not recognized!"),
+        | Operator::GreaterThanEqual => eval_infix_comp(left, operator, right),
     }
 }
 
-/// Evaluates arithmetic operations that are of the infix notation.
-fn eval_infix_op(left: Box<Expr>, operator: Operator, right: Box<Expr>, env: &mut Env) -> Object {
-    match operator {
-        // Inner workings, eval left side and right side then check if both are numbers (or similar type)
-        Operator::Plus => match (eval_expr(*left, env), eval_expr(*right, env)) {
-            (Object::Integer(left), Object::Integer(right)) => Object::Integer(left + right),
-            _ => panic!("Unsupported operation between two expressions"),
-        },
-        Operator::Minus => match (eval_expr(*left, env), eval_expr(*right, env)) {
-            (Object::Integer(left), Object::Integer(right)) => Object::Integer(left - right),
-            _ => panic!("Unsupported operation between two expressions"),
-        },
-        Operator::Multiply => match (eval_expr(*left, env), eval_expr(*right, env)) {
-            (Object::Integer(left), Object::Integer(right)) => Object::Integer(left * right),
-            _ => panic!("Unsupported operation between two expressions"),
-        },
-        Operator::Divide => match (eval_expr(*left, env), eval_expr(*right, env)) {
-            (Object::Integer(left), Object::Integer(right)) => Object::Integer(left / right),
-            _ => panic!("Unsupported operation between two expressions"),
-        },
-
-        Operator::Modulo => match (eval_expr(*left, env), eval_expr(*right, env)) {
-            (Object::Integer(left), Object::Integer(right)) => Object::Integer(left % right),
-            _ => panic!("Unsupported operation between two expressions"),
-        },
-
-        _ => panic!("Unsupported arithmetic operator found "),
-    }
+/// Describes an operator applied to objects that don't support it.
+fn unsupported(left: &Object, operator: Operator, right: &Object) -> RuntimeError {
+    RuntimeError::TypeMismatch(format!(
+        "Unsupported operation {:?} between {} and {}",
+        operator, left, right
+    ))
 }
 
-/// Evaluates comparisons that are of the infix notation.
-fn eval_infix_comp(left: Box<Expr>, operator: Operator, right: Box<Expr>, env: &mut Env) -> Object {
-    match operator {
-        Operator::Equals => match (eval_expr(*left, env), eval_expr(*right, env)) {
-            (Object::Integer(left), Object::Integer(right)) => Object::Boolean(left == right),
-            (Object::String(left), Object::String(right)) => Object::Boolean(left == right),
-            _ => panic!("Unsupported operation between two expressions"),
-        },
-        Operator::LessThan => match (eval_expr(*left, env), eval_expr(*right, env)) {
-            (Object::Integer(left), Object::Integer(right)) => Object::Boolean(left < right),
-            _ => panic!("Unsupported operation between two expressions"),
-        },
-        Operator::GreaterThan => match (eval_expr(*left, env), eval_expr(*right, env)) {
-            (Object::Integer(left), Object::Integer(right)) => Object::Boolean(left > right),
-            _ => panic!("Unsupported operation between two expressions"),
-        },
-        Operator::GreaterThanEqual => match (eval_expr(*left, env), eval_expr(*right, env)) {
-            (Object::Integer(left), Object::Integer(right)) => Object::Boolean(left >= right),
-            _ => panic!("Unsupported operation between two expressions"),
-        },
-        Operator::LessThanEqual => match (eval_expr(*left, env), eval_expr(*right, env)) {
-            (Object::Integer(left), Object::Integer(right)) => Object::Boolean(left <= right),
-            _ => panic!("Unsupported operation between two expressions"),
-        },
-        Operator::NotEquals => match (eval_expr(*left, env), eval_expr(*right, env)) {
-            (Object::Integer(left), Object::Integer(right)) => Object::Boolean(left != right),
-            (Object::String(left), Object::String(right)) => Object::Boolean(left != right),
-            _ => panic!("Unsupported operation between two expressions"),
-        },
-
-        _ => panic!("Unsupported comparison operator encountered"),
-    }
-}
+/// Evaluates arithmetic operations that are of the infix notation.
+fn eval_infix_op(left: Object, operator: Operator, right: Object) -> Result<Object, RuntimeError> {
+    let (left_num, right_num) = match (&left, &right) {
+        (Object::Integer(left), Object::Integer(right)) => (*left, *right),
+        _ => return Err(unsupported(&left, operator, &right)),
+    };
 
-/// Evaluates builtins that come from the Builtin call.
-///
-/// # Current Builtins
-/// - len (prints the length of strings)
-/// - quwuit (terminates the program)
-/// - dprint (prints a statement)
-fn eval_builtin(func_name: String, args: Vec<Object>) -> Object {
-    match &func_name as &str {
-        "len" => len(args),
-        "quwuit" => Object::Terminate,
-        "dprint" => dprint(args),
-        _ => panic!("inbuilt not found!"),
+    match operator {
+        // Inner workings, eval left side and right side then check if both are numbers (or similar type)
+        Operator::Divide | Operator::Modulo if right_num == 0 => Err(RuntimeError::DivisionByZero),
+        Operator::Plus => checked(left_num.checked_add(right_num)),
+        Operator::Minus => checked(left_num.checked_sub(right_num)),
+        Operator::Multiply => checked(left_num.checked_mul(right_num)),
+        Operator::Divide => checked(left_num.checked_div(right_num)),
+        Operator::Modulo => checked(left_num.checked_rem(right_num)),
+        _ => Err(unsupported(&left, operator, &right)),
     }
 }
 
-/// BUILTIN - len
-fn len(args: Vec<Object>) -> Object {
-    match args.as_slice() {
-        [Object::String(string)] => Object::Integer(string.len() as i64),
-        _ => panic!("Non measurable type given"),
-    }
+/// Turns the result of checked arithmetic into an integer, or an error if it overflowed.
+fn checked(result: Option<i64>) -> Result<Object, RuntimeError> {
+    result
+        .map(Object::Integer)
+        .ok_or(RuntimeError::IntegerOverflow)
 }
 
-/// BUILTIN - dprint
-fn dprint(args: Vec<Object>) -> Object {
-    match args.as_slice() {
-        [obj] => Object::Print(Box::new(obj.clone())),
-        _ => panic!("Incorrect args supplied"),
+/// Evaluates comparisons that are of the infix notation.
+fn eval_infix_comp(
+    left: Object,
+    operator: Operator,
+    right: Object,
+) -> Result<Object, RuntimeError> {
+    match (operator, &left, &right) {
+        (Operator::Equals, Object::Integer(left), Object::Integer(right)) => {
+            Ok(Object::Boolean(left == right))
+        }
+        (Operator::Equals, Object::String(left), Object::String(right)) => {
+            Ok(Object::Boolean(left == right))
+        }
+        (Operator::NotEquals, Object::Integer(left), Object::Integer(right)) => {
+            Ok(Object::Boolean(left != right))
+        }
+        (Operator::NotEquals, Object::String(left), Object::String(right)) => {
+            Ok(Object::Boolean(left != right))
+        }
+        (Operator::LessThan, Object::Integer(left), Object::Integer(right)) => {
+            Ok(Object::Boolean(left < right))
+        }
+        (Operator::GreaterThan, Object::Integer(left), Object::Integer(right)) => {
+            Ok(Object::Boolean(left > right))
+        }
+        (Operator::GreaterThanEqual, Object::Integer(left), Object::Integer(right)) => {
+            Ok(Object::Boolean(left >= right))
+        }
+        (Operator::LessThanEqual, Object::Integer(left), Object::Integer(right)) => {
+            Ok(Object::Boolean(left <= right))
+        }
+        _ => Err(unsupported(&left, operator, &right)),
     }
 }
@@ -0,0 +1,47 @@
+//! Handles where a program reads its input from, i.e nya_input.
+use crate::eval::error::RuntimeError;
+use std::collections::VecDeque;
+use std::io;
+
+/// A source of lines typed in by the user.
+pub trait Input {
+    /// Reads a single line without its line break, returning None once the input is exhausted.
+    fn read_line(&mut self) -> Result<Option<String>, RuntimeError>;
+}
+
+/// Reads from standard input, which is the default for the REPL and interpreter.
+pub struct StdInput;
+
+impl Input for StdInput {
+    fn read_line(&mut self) -> Result<Option<String>, RuntimeError> {
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) => Ok(None), // EOF
+            Ok(_) => Ok(Some(line.trim_end_matches(&['\n', '\r'][..]).to_string())),
+            Err(error) => Err(RuntimeError::Custom(format!(
+                "could not read input: {}",
+                error
+            ))),
+        }
+    }
+}
+
+/// Feeds a fixed list of lines, useful for tests or embedding where there is no user to type.
+pub struct ScriptedInput {
+    lines: VecDeque<String>,
+}
+
+impl ScriptedInput {
+    /// Instantiates an input that returns each line in order, then EOF.
+    pub fn new<S: Into<String>>(lines: Vec<S>) -> Self {
+        ScriptedInput {
+            lines: lines.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Input for ScriptedInput {
+    fn read_line(&mut self) -> Result<Option<String>, RuntimeError> {
+        Ok(self.lines.pop_front())
+    }
+}
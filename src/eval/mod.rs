@@ -1,5 +1,11 @@
 //! The eval module evaluates parsed statements and returns object enums.
 //!
 
+pub mod builtins;
+pub mod context;
 pub mod env;
+pub mod error;
 pub mod eval;
+pub mod input;
+pub mod native;
+pub mod output;
@@ -0,0 +1,173 @@
+//! Native functions are written in Rust and exposed to programs by the host. They are looked up by name when a variable is not bound in the environment, which makes them ordinary callable values.
+use crate::eval::builtins;
+use crate::eval::context::Context;
+use crate::eval::error::RuntimeError;
+use crate::eval::eval::{ControlFlow, Object};
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+/// The number of arguments a native function accepts.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Arity {
+    Exact(usize),
+    Between(usize, usize),
+}
+
+impl Arity {
+    /// Checks whether a call with the given number of arguments is allowed.
+    pub fn accepts(&self, count: usize) -> bool {
+        match self {
+            Arity::Exact(num) => count == *num,
+            Arity::Between(min, max) => *min <= count && count <= *max,
+        }
+    }
+}
+
+impl From<usize> for Arity {
+    fn from(num: usize) -> Self {
+        Arity::Exact(num)
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arity::Exact(num) => num.fmt(f),
+            Arity::Between(min, max) => write!(f, "{} to {}", min, max),
+        }
+    }
+}
+
+/// The signature of a function registered by the host.
+pub type NativeFn = dyn Fn(&[Object]) -> Result<Object, RuntimeError>;
+
+/// The signature of an intrinsic, a native function that needs the context to perform side effects such as printing or terminating.
+pub type IntrinsicFn = fn(&[Object], &mut Context) -> Result<Object, ControlFlow>;
+
+#[derive(Clone)]
+enum NativeBody {
+    Native(Rc<NativeFn>),
+    Intrinsic(IntrinsicFn),
+}
+
+/// A named native function along with its arity, which is checked before every call.
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: Arity,
+    body: NativeBody,
+}
+
+impl NativeFunction {
+    /// Calls the function after checking the number of arguments.
+    pub(crate) fn call(&self, args: &[Object], ctx: &mut Context) -> Result<Object, ControlFlow> {
+        if !self.arity.accepts(args.len()) {
+            return Err(ControlFlow::Error(RuntimeError::WrongArity {
+                name: self.name.clone(),
+                expected: self.arity,
+                found: args.len(),
+            }));
+        }
+        match &self.body {
+            NativeBody::Native(func) => Ok(func(args)?),
+            NativeBody::Intrinsic(func) => func(args, ctx),
+        }
+    }
+}
+
+// Native functions are compared and printed by name, since closures can't be.
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.arity == other.arity
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NativeFunction({})", self.name)
+    }
+}
+
+/// Holds every native function available to a program. The host populates it before running.
+///
+/// # Examples
+///
+/// ```
+/// # use uwucode::{NativeRegistry, Object, RuntimeError};
+/// let mut natives = NativeRegistry::with_builtins();
+/// natives.register("double", 1, |args| match args {
+///     [Object::Integer(num)] => Ok(Object::Integer(num * 2)),
+///     _ => Err(RuntimeError::Custom(String::from("double expects an integer"))),
+/// });
+/// ```
+#[derive(Clone, Default)]
+pub struct NativeRegistry {
+    functions: HashMap<String, NativeFunction>,
+}
+
+impl NativeRegistry {
+    /// Instantiates a registry with no functions at all.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Instantiates a registry with the standard builtins, i.e len and dprint.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        builtins::register(&mut registry);
+        registry
+    }
+
+    /// Registers a function, replacing any existing function of the same name.
+    pub fn register<A, F>(&mut self, name: &str, arity: A, func: F)
+    where
+        A: Into<Arity>,
+        F: Fn(&[Object]) -> Result<Object, RuntimeError> + 'static,
+    {
+        self.insert(name, arity.into(), NativeBody::Native(Rc::new(func)));
+    }
+
+    /// Registers an intrinsic, replacing any existing function of the same name.
+    pub(crate) fn register_intrinsic<A: Into<Arity>>(
+        &mut self,
+        name: &str,
+        arity: A,
+        func: IntrinsicFn,
+    ) {
+        self.insert(name, arity.into(), NativeBody::Intrinsic(func));
+    }
+
+    fn insert(&mut self, name: &str, arity: Arity, body: NativeBody) {
+        self.functions.insert(
+            String::from(name),
+            NativeFunction {
+                name: String::from(name),
+                arity,
+                body,
+            },
+        );
+    }
+
+    /// Instantiates a registry with the standard builtins, along with the builtins that read the script's arguments and environment.
+    pub fn with_system(script_args: Vec<String>) -> Self {
+        let mut registry = Self::with_builtins();
+        builtins::register_system(&mut registry, script_args);
+        registry
+    }
+
+    /// Removes a function so programs can no longer call it, returning it if it was registered.
+    pub fn remove(&mut self, name: &str) -> Option<NativeFunction> {
+        self.functions.remove(name)
+    }
+
+    /// Looks up a function by name.
+    pub fn get(&self, name: &str) -> Option<&NativeFunction> {
+        self.functions.get(name)
+    }
+
+    /// Lists the names of every registered function, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.functions.keys()
+    }
+}
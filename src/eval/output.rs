@@ -0,0 +1,81 @@
+//! Handles where the output of a program, i.e dprint, is written to.
+use crate::eval::error::RuntimeError;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::rc::Rc;
+
+/// A destination for text printed by a program.
+pub trait Output {
+    /// Writes text as is, for instance an input prompt.
+    fn write(&mut self, text: &str) -> Result<(), RuntimeError>;
+
+    /// Writes a single line, adding the line break.
+    fn write_line(&mut self, line: &str) -> Result<(), RuntimeError> {
+        self.write(&format!("{}\n", line))
+    }
+}
+
+/// Describes an output that could not be written to.
+fn write_failed(error: io::Error) -> RuntimeError {
+    RuntimeError::Custom(format!("could not write output: {}", error))
+}
+
+/// Writes to standard output, which is the default for the REPL and interpreter.
+pub struct StdOutput;
+
+impl Output for StdOutput {
+    fn write(&mut self, text: &str) -> Result<(), RuntimeError> {
+        let mut stdout = io::stdout();
+        stdout.write_all(text.as_bytes()).map_err(write_failed)?;
+        // Prompts don't end in a line break, so flush to make sure they show up before reading.
+        stdout.flush().map_err(write_failed)
+    }
+}
+
+/// Collects output in memory. Clones share the same buffer, so a clone can be handed to the evaluator while the original is kept to read the output back.
+#[derive(Clone, Default)]
+pub struct BufferOutput {
+    buffer: Rc<RefCell<String>>,
+}
+
+impl BufferOutput {
+    /// Instantiates an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns everything written so far.
+    pub fn contents(&self) -> String {
+        self.buffer.borrow().clone()
+    }
+}
+
+impl Output for BufferOutput {
+    fn write(&mut self, text: &str) -> Result<(), RuntimeError> {
+        self.buffer.borrow_mut().push_str(text);
+        Ok(())
+    }
+}
+
+/// Writes output to a file, replacing any existing contents.
+pub struct FileOutput {
+    file: File,
+}
+
+impl FileOutput {
+    /// Creates or truncates the file at the given path.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(FileOutput {
+            file: File::create(path)?,
+        })
+    }
+}
+
+impl Output for FileOutput {
+    fn write(&mut self, text: &str) -> Result<(), RuntimeError> {
+        self.file.write_all(text.as_bytes()).map_err(write_failed)
+    }
+}
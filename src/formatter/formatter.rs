@@ -0,0 +1,320 @@
+//! Formats source from the concrete syntax tree, which keeps the comments the AST drops.
+//!
+//! Tokens are printed in source order, and the kind of node each belongs to decides how it is laid out, i.e whether a brace opens a block or a map.
+use crate::lexer::trivia::{SyntaxToken, Trivia};
+use crate::parser::cst::{parse_cst, SyntaxElement, SyntaxKind, SyntaxNode};
+use crate::parser::error::ParseError;
+use crate::token::token::Token;
+use std::str::FromStr;
+
+const INDENT: &str = "    ";
+
+/// How statements are ended in formatted source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Terminator {
+    /// `;`
+    Semicolon,
+    /// `:3`
+    Uwu,
+}
+
+impl FromStr for Terminator {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            ";" => Ok(Terminator::Semicolon),
+            ":3" => Ok(Terminator::Uwu),
+            _ => Err(format!("{} is not a terminator, use ; or :3", text)),
+        }
+    }
+}
+
+impl Terminator {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Terminator::Semicolon => ";",
+            Terminator::Uwu => ":3",
+        }
+    }
+}
+
+/// Choices left to the user, everything else about the style is fixed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FormatOptions {
+    pub terminator: Terminator,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            terminator: Terminator::Semicolon,
+        }
+    }
+}
+
+/// Formats a program, or returns the error that stops it from parsing. Source that does not parse is never rewritten.
+///
+/// # Technical Information
+/// Blocks are indented by four spaces with one statement per line, and rawr starts a new line after the closing brace of nuzzles.
+/// Binary operators are surrounded by spaces while calls, indexing and unary operators are not. Comments stay on their own line or at the end of the line they were on, and runs of blank lines become a single one.
+///
+/// # Examples
+/// ```ignore
+/// let formatted = format_source("owo x=1+2:3", &FormatOptions::default()).unwrap();
+/// assert_eq!(formatted, "owo x = 1 + 2;\n");
+/// ```
+pub fn format_source(source: &str, options: &FormatOptions) -> Result<String, ParseError> {
+    let tree = parse_cst(source)?;
+    let mut tokens = vec![];
+    flatten(&tree, &mut tokens);
+    let mut printer = Printer::new(options);
+    let mut index = 0;
+    while index < tokens.len() {
+        let (token, parent) = tokens[index];
+        let gap = printer.trivia(&token.leading);
+        if token.token == Token::EOF {
+            break;
+        }
+        let mut next = index + 1;
+        let following = tokens.get(next).map(|(token, _)| *token);
+        // An empty block is kept on one line, so its closing brace is printed along with the opening one.
+        if opens_block(&token.token, parent)
+            && following
+                .is_some_and(|closing| closing.token == Token::RBRA && !has_comment(closing))
+        {
+            printer.token(&token.token, "{", &gap, parent);
+            printer.token(&Token::RBRA, "}", "", parent);
+            next += 1;
+        } else {
+            printer.token(&token.token, &token.text, &gap, parent);
+        }
+        // rawr goes on the line after the brace that closes nuzzles.
+        if printer.prev == Some(Token::RBRA)
+            && tokens.get(next).map(|(token, _)| &token.token) == Some(&Token::ELSE)
+        {
+            printer.pending_break = true;
+        }
+        index = next;
+    }
+    Ok(printer.finish())
+}
+
+/// Lists the tokens of a tree in source order, each along with the kind of node it belongs to.
+fn flatten<'a>(node: &'a SyntaxNode, tokens: &mut Vec<(&'a SyntaxToken, SyntaxKind)>) {
+    for child in node.children() {
+        match child {
+            SyntaxElement::Node(inner) => flatten(inner, tokens),
+            SyntaxElement::Token(token) => tokens.push((token, node.kind())),
+        }
+    }
+}
+
+/// Blocks follow the parameters or condition of uwu, nuzzles, nyaa and fur, or rawr. A brace anywhere else starts a map.
+fn opens_block(token: &Token, parent: SyntaxKind) -> bool {
+    *token == Token::LBRA && parent == SyntaxKind::Block
+}
+
+fn has_comment(token: &SyntaxToken) -> bool {
+    token
+        .leading
+        .iter()
+        .any(|trivia| matches!(trivia, Trivia::Comment(_)))
+}
+
+/// Checks whether source is already formatted, so formatting it would change nothing.
+#[cfg(test)]
+pub fn is_formatted(source: &str, options: &FormatOptions) -> Result<bool, ParseError> {
+    Ok(format_source(source, options)? == source)
+}
+
+/// What an open bracket started, which decides how its contents are laid out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Bracket {
+    Block,
+    Map,
+    Other,
+}
+
+/// Writes tokens out one at a time, tracking the indentation and the brackets they are nested in.
+struct Printer<'a> {
+    options: &'a FormatOptions,
+    output: String,
+    brackets: Vec<Bracket>,
+    indent: usize,
+    /// Nothing has been written on the current line yet.
+    line_start: bool,
+    /// The next token starts a new line.
+    pending_break: bool,
+    /// The last token written, not counting comments.
+    prev: Option<Token>,
+    prev_unary: bool,
+    after_comment: bool,
+}
+
+impl<'a> Printer<'a> {
+    fn new(options: &'a FormatOptions) -> Self {
+        Printer {
+            options,
+            output: String::new(),
+            brackets: vec![],
+            indent: 0,
+            line_start: true,
+            pending_break: false,
+            prev: None,
+            prev_unary: false,
+            after_comment: false,
+        }
+    }
+
+    /// Writes out the comments in the trivia before a token, returning the whitespace left between the last of them and the token.
+    fn trivia(&mut self, leading: &[Trivia]) -> String {
+        let mut gap = String::new();
+        for trivia in leading {
+            match trivia {
+                Trivia::Whitespace(text) => gap.push_str(text),
+                // A comment left open at the end of the source gets closed.
+                Trivia::Comment(text) if text.len() < 4 || !text.ends_with("*/") => {
+                    self.comment(&format!("{}*/", text), &gap);
+                    gap.clear();
+                }
+                Trivia::Comment(text) => {
+                    self.comment(text, &gap);
+                    gap.clear();
+                }
+            }
+        }
+        gap
+    }
+
+    fn token(&mut self, token: &Token, text: &str, gap: &str, parent: SyntaxKind) {
+        let closes_block = *token == Token::RBRA && self.brackets.last() == Some(&Bracket::Block);
+        if closes_block {
+            self.indent -= 1;
+            // Only an empty block closes straight after opening.
+            self.pending_break = self.prev != Some(Token::LBRA) || self.after_comment;
+        }
+        if self.pending_break {
+            let opened_block = self.prev == Some(Token::LBRA);
+            self.line_break(gap, !opened_block && !closes_block);
+        } else if !self.line_start && (self.after_comment || self.needs_space(token, parent)) {
+            self.output.push(' ');
+        }
+
+        match token {
+            Token::SEMICOLON => {
+                self.write(self.options.terminator.as_str());
+                self.pending_break = true;
+            }
+            Token::LBRA if opens_block(token, parent) => {
+                self.write(text);
+                self.brackets.push(Bracket::Block);
+                self.indent += 1;
+                self.pending_break = true;
+            }
+            Token::LBRA => {
+                self.write(text);
+                self.brackets.push(Bracket::Map);
+            }
+            Token::LPAR | Token::LBRACKET => {
+                self.write(text);
+                self.brackets.push(Bracket::Other);
+            }
+            Token::RBRA | Token::RPAR | Token::RBRACKET => {
+                self.write(text);
+                self.brackets.pop();
+            }
+            _ => self.write(text),
+        }
+
+        self.prev_unary = parent == SyntaxKind::Prefix;
+        self.prev = Some(token.clone());
+        self.after_comment = false;
+    }
+
+    /// Comments that started a line in the source keep a line to themselves, others trail whatever came before them.
+    fn comment(&mut self, text: &str, gap: &str) {
+        if self.output.is_empty() {
+            self.write(text);
+            self.pending_break = true;
+        } else if gap.contains('\n') {
+            self.line_break(gap, self.prev != Some(Token::LBRA));
+            self.write(text);
+            self.pending_break = true;
+        } else {
+            if !self.line_start {
+                self.output.push(' ');
+            }
+            self.write(text);
+        }
+        self.after_comment = true;
+    }
+
+    /// Ends the current line, leaving a blank line if the source had at least one and it is allowed here.
+    fn line_break(&mut self, gap: &str, allow_blank: bool) {
+        if !self.line_start {
+            self.output.push('\n');
+        }
+        if allow_blank && gap.matches('\n').count() > 1 {
+            self.output.push('\n');
+        }
+        self.line_start = true;
+        self.pending_break = false;
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.line_start {
+            for _ in 0..self.indent {
+                self.output.push_str(INDENT);
+            }
+            self.line_start = false;
+        }
+        self.output.push_str(text);
+    }
+
+    /// Decides whether a token on the same line is separated from the one before it.
+    fn needs_space(&self, token: &Token, parent: SyntaxKind) -> bool {
+        let prev = match &self.prev {
+            Some(prev) => prev,
+            None => return false,
+        };
+        match token {
+            Token::COMMA | Token::SEMICOLON | Token::COLON | Token::RPAR | Token::RBRACKET => false,
+            // Closing braces of blocks that are not empty start their own line.
+            Token::RBRA => false,
+            // Calls, parameters and indexing.
+            Token::LPAR
+                if matches!(parent, SyntaxKind::ArgumentList | SyntaxKind::ParameterList) =>
+            {
+                false
+            }
+            Token::LBRACKET if parent == SyntaxKind::Index => false,
+            _ => !(self.prev_unary || matches!(prev, Token::LPAR | Token::LBRACKET | Token::LBRA)),
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if !self.output.is_empty() && !self.line_start {
+            self.output.push('\n');
+        }
+        self.output
+    }
+}
+
+/// The first line, counting from 1, where source differs from its formatted version. Used to point --check at the problem.
+pub fn first_changed_line(source: &str, formatted: &str) -> Option<usize> {
+    if source == formatted {
+        return None;
+    }
+    let mut before = source.lines();
+    let mut after = formatted.lines();
+    let mut line = 1;
+    // A difference in line endings alone is reported on the last line.
+    loop {
+        match (before.next(), after.next()) {
+            (Some(a), Some(b)) if a == b => line += 1,
+            (None, None) => return Some(line.max(2) - 1),
+            _ => return Some(line),
+        }
+    }
+}
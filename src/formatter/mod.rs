@@ -0,0 +1,3 @@
+//! The formatter rewrites uwucode source in a single consistent style, keeping its comments.
+
+pub mod formatter;
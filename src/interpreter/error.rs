@@ -0,0 +1,68 @@
+//! Errors returned to the host by the embedding API.
+use crate::eval::eval::RuntimeError;
+use crate::parser::error::ParseError;
+use crate::vm::error::LoadError;
+use std::error;
+use std::fmt;
+use std::io;
+
+/// Wraps every way running uwucode from a host can fail.
+#[derive(Debug)]
+pub enum Error {
+    /// A source file could not be read.
+    Io(io::Error),
+    Parse(ParseError),
+    /// A compiled program could not be loaded.
+    Load(LoadError),
+    Runtime(RuntimeError),
+    /// The program called quwuit before it finished, carrying the exit code it asked for.
+    Terminated(i32),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "IO error: {}", error),
+            Error::Parse(error) => write!(f, "Parser error: {}", error),
+            Error::Load(error) => write!(f, "Load error: {}", error),
+            Error::Runtime(error) => write!(f, "Runtime error: {}", error),
+            Error::Terminated(code) => write!(f, "program terminated with exit code {}", code),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::Parse(error) => Some(error),
+            Error::Load(error) => Some(error),
+            Error::Runtime(error) => Some(error),
+            Error::Terminated(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::Parse(error)
+    }
+}
+
+impl From<LoadError> for Error {
+    fn from(error: LoadError) -> Self {
+        Error::Load(error)
+    }
+}
+
+impl From<RuntimeError> for Error {
+    fn from(error: RuntimeError) -> Self {
+        Error::Runtime(error)
+    }
+}
@@ -0,0 +1,198 @@
+//! A stable entry point for running uwucode from other Rust programs.
+use crate::eval::eval::{
+    call_object, eval_return, Context, ControlFlow, Env, Object, RuntimeError,
+};
+use crate::eval::native::NativeRegistry;
+use crate::interpreter::error::Error;
+use crate::lexer::lexer::Lexer;
+use crate::parser::parser::parse;
+use crate::vm::bytecode::Program;
+use crate::vm::compiler::compile;
+use crate::vm::uwuc::{decode, is_compiled};
+use crate::vm::vm;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+use std::str::FromStr;
+
+/// How programs are run. Both backends give the same output, values and errors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    /// Evaluates the syntax tree directly.
+    TreeWalker,
+    /// Compiles to bytecode and runs it on the VM.
+    Vm,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "tree" => Ok(Backend::TreeWalker),
+            "vm" => Ok(Backend::Vm),
+            _ => Err(format!("{} is not a backend, use tree or vm", text)),
+        }
+    }
+}
+
+/// Owns a global environment and a context, so definitions persist between calls.
+///
+/// ```
+/// use uwucode::{Interpreter, Object};
+///
+/// let mut interpreter = Interpreter::new();
+/// interpreter.eval_str("uwu double(x) { sugoi x * 2; };").unwrap();
+/// interpreter.set_global("n", Object::Integer(21));
+/// assert_eq!(interpreter.eval_str("double(n);").unwrap(), Object::Integer(42));
+/// ```
+pub struct Interpreter {
+    env: Env,
+    ctx: Context,
+    backend: Backend,
+}
+
+impl Interpreter {
+    /// Instantiates an interpreter that prints to standard output and reads from standard input.
+    pub fn new() -> Self {
+        Self::with_context(Context::new())
+    }
+
+    /// Instantiates an interpreter with the given output, input and natives.
+    pub fn with_context(ctx: Context) -> Self {
+        Interpreter {
+            env: Env::new(),
+            ctx,
+            backend: Backend::TreeWalker,
+        }
+    }
+
+    /// Gives access to the context, for instance to register more natives.
+    pub fn context(&mut self) -> &mut Context {
+        &mut self.ctx
+    }
+
+    /// Picks how later calls to eval_str run programs.
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend;
+    }
+
+    /// Runs a program in the global environment and returns the value of its last statement, or the value given to a top level sugoi.
+    ///
+    /// If the program fails, anything it defined is rolled back so globals are left as they were. Side effects such as printing can't be undone.
+    pub fn eval_str(&mut self, source: &str) -> Result<Object, Error> {
+        let mut token_vec = Lexer::new(source).lex();
+        let parsed = parse(&mut token_vec)?;
+        match self.backend {
+            Backend::TreeWalker => self.guarded(|env, ctx| eval_return(parsed, env, ctx)),
+            Backend::Vm => self.run_program(compile(&parsed)),
+        }
+    }
+
+    /// Loads a program compiled with uwucode compile and runs it on the VM, whichever backend is picked, with the same rollback as eval_str.
+    pub fn eval_program(&mut self, bytes: &[u8]) -> Result<Object, Error> {
+        self.run_program(decode(bytes)?)
+    }
+
+    /// Runs a program that has already been compiled or loaded.
+    pub(crate) fn run_program(&mut self, program: Program) -> Result<Object, Error> {
+        let program = Rc::new(program);
+        self.guarded(|env, ctx| vm::run(program, env, ctx))
+    }
+
+    /// Runs a program, rolling back the global environment if it fails.
+    fn guarded<F>(&mut self, run: F) -> Result<Object, Error>
+    where
+        F: FnOnce(&mut Env, &mut Context) -> Result<Object, ControlFlow>,
+    {
+        let snapshot = self.env.clone();
+        self.ctx.reset_call_depth();
+        let result = run(&mut self.env, &mut self.ctx).map_err(Error::from);
+        if let Err(Error::Runtime(_)) = result {
+            self.env = snapshot;
+        }
+        result
+    }
+
+    /// Reads a program from a file and runs it like eval_str. Files compiled with uwucode compile are loaded and run on the VM.
+    pub fn eval_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Object, Error> {
+        let bytes = fs::read(path)?;
+        if is_compiled(&bytes) {
+            return self.eval_program(&bytes);
+        }
+        let source = String::from_utf8(bytes)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        self.eval_str(&source)
+    }
+
+    /// Gives access to the natives a program can call.
+    pub fn natives(&self) -> &NativeRegistry {
+        self.ctx.natives()
+    }
+
+    /// Gives access to the global environment, for instance to list what has been defined.
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    /// Replaces the global environment, i.e to restore a copy of env taken earlier.
+    pub fn set_env(&mut self, env: Env) {
+        self.env = env;
+    }
+
+    /// Forgets everything defined so far. The context, and so any registered natives, is kept.
+    pub fn reset(&mut self) {
+        self.env = Env::new();
+    }
+
+    /// Binds a variable in the global environment.
+    pub fn set_global(&mut self, name: &str, value: Object) {
+        self.env.set(String::from(name), value);
+    }
+
+    /// Looks up a variable or function defined in the global environment.
+    pub fn get_global(&self, name: &str) -> Option<Object> {
+        self.env.get(name)
+    }
+
+    /// Calls a global function, or a native if no global has the name.
+    pub fn call_function(&mut self, name: &str, args: Vec<Object>) -> Result<Object, Error> {
+        let callee = match self.get_global(name) {
+            Some(value) => value,
+            None => match self.ctx.natives().get(name) {
+                Some(native) => Object::Native(native.clone()),
+                None => {
+                    return Err(Error::Runtime(RuntimeError::UndefinedVariable(
+                        String::from(name),
+                    )))
+                }
+            },
+        };
+        call_object(
+            String::from(name),
+            callee,
+            args,
+            &mut self.env,
+            &mut self.ctx,
+        )
+        .map_err(Error::from)
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<ControlFlow> for Error {
+    fn from(signal: ControlFlow) -> Self {
+        match signal {
+            ControlFlow::Error(error) => Error::Runtime(error),
+            ControlFlow::Terminate(code) => Error::Terminated(code),
+            // Returns are caught by eval_return, and the parser keeps bweak and continyue inside of loops.
+            signal => unreachable!("{:?} escaped to the top level", signal),
+        }
+    }
+}
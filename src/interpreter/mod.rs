@@ -0,0 +1,4 @@
+//! The interpreter module exposes uwucode to Rust programs that embed it.
+
+pub mod error;
+pub mod interpreter;
@@ -1,5 +1,7 @@
+use crate::lexer::trivia::{SyntaxToken, Trivia};
 use crate::token::token::{lookup_ident, Token};
 use ::std::iter::Peekable;
+use std::ops::Range;
 use std::str;
 use std::str::Chars;
 
@@ -9,6 +11,10 @@ pub struct Lexer<'a> {
     Info: <'a> indicates a speciifed lifetime.
     */
     pub chr_iter: Peekable<Chars<'a>>,
+    source: &'a str,
+    pos: usize,          // Byte offset of the next character.
+    token_start: usize,  // Byte offset of the last token read.
+    keep_comments: bool, // Comments are skipped unless tooling asks for them.
 }
 
 /// Initializes an instance of a lexer which returns a vector of tokens on a string.
@@ -16,14 +22,18 @@ pub struct Lexer<'a> {
 /// # Examples
 ///
 /// Initialize a lexer as follows:
-/// ```
+/// ```ignore
 /// let mut lexer = Lexer::new("owo five = 5;");
 /// ```
 impl<'a> Lexer<'a> {
     /// Calls next on the char iterator.
     pub fn read_char(&mut self) -> Option<char> {
         // Returns the next item in the iterator.
-        self.chr_iter.next()
+        let chr = self.chr_iter.next();
+        if let Some(chr) = chr {
+            self.pos += chr.len_utf8();
+        }
+        chr
     }
 
     pub fn peek_char(&mut self) -> Option<&char> {
@@ -34,9 +44,25 @@ impl<'a> Lexer<'a> {
     pub fn new(file_string: &'a str) -> Lexer<'a> {
         Lexer {
             chr_iter: file_string.chars().peekable(),
+            source: file_string,
+            pos: 0,
+            token_start: 0,
+            keep_comments: false,
         }
     }
 
+    /// Instantiates a lexer that returns comments as tokens rather than skipping them, for tools such as the formatter. The parser does not accept comment tokens.
+    pub fn with_comments(file_string: &'a str) -> Lexer<'a> {
+        let mut lexer = Lexer::new(file_string);
+        lexer.keep_comments = true;
+        lexer
+    }
+
+    /// The byte range of the source the last token was read from. Whitespace and comments before it are not included.
+    pub fn span(&self) -> Range<usize> {
+        self.token_start..self.pos
+    }
+
     /// Consumes all whitespace characters by peaking, making the language independent of whitespace.
     pub fn skip_whitespace(&mut self) {
         // While not perfect, it's better to use Some over unwrap to avoid None issues.
@@ -80,8 +106,8 @@ impl<'a> Lexer<'a> {
         expression
     }
 
-    /// Reads in a sequence of integers and returns an integer.
-    pub fn read_number(&mut self, first_chr: char) -> i64 {
+    /// Reads in a sequence of integers and returns an integer, or the digits if they are too large to fit in one.
+    pub fn read_number(&mut self, first_chr: char) -> Result<i64, String> {
         // TODO: Prefix notation, i.e 0x, 0b, 0o, 0f
         let mut expression: String = String::from(first_chr);
 
@@ -93,12 +119,13 @@ impl<'a> Lexer<'a> {
             }
         }
 
-        expression.parse().unwrap()
+        expression.parse().map_err(|_| expression)
     }
 
     /// Reads from the iterator to create the next token.
     pub fn next_token(&mut self) -> Token {
         self.skip_whitespace();
+        self.token_start = self.pos;
 
         /*
         Matching process:
@@ -109,7 +136,7 @@ impl<'a> Lexer<'a> {
             // = first, into ASSIGN and EQUAL
             Some('=') => {
                 // Comparison operator
-                if self.peek_char().unwrap() == &'=' {
+                if self.peek_char() == Some(&'=') {
                     self.read_char();
                     Token::EQ
                 } else {
@@ -119,13 +146,13 @@ impl<'a> Lexer<'a> {
             Some(',') => Token::COMMA,
             Some(';') => Token::SEMICOLON,
 
-            // Alternative (or soon to be default) line end :3
+            // Alternative (or soon to be default) line end :3, otherwise a map key separator
             Some(':') => {
-                if self.peek_char().unwrap() == &'3' {
+                if self.peek_char() == Some(&'3') {
                     self.read_char();
                     Token::SEMICOLON
                 } else {
-                    Token::ILLEGAL(String::from(":"))
+                    Token::COLON
                 }
             }
 
@@ -137,18 +164,26 @@ impl<'a> Lexer<'a> {
             Some('*') => Token::ASTERISK,
             // TODO: implement integer division, comments
             Some('/') => {
-                if self.peek_char().unwrap() == &'*' {
+                if self.peek_char() == Some(&'*') {
                     // comments
                     self.read_char();
+                    let mut comment = String::new();
                     loop {
-                        if self.peek_char().unwrap() == &'*' {
-                            self.read_char();
-                            if self.peek_char().unwrap() == &'/' {
+                        match self.read_char() {
+                            Some('*') if self.peek_char() == Some(&'/') => {
                                 self.read_char();
-                                return self.next_token();
+                                break;
                             }
+                            Some(chr) => comment.push(chr),
+                            // An unclosed comment runs to the end of the input.
+                            None if self.keep_comments => break,
+                            None => return Token::EOF,
                         }
-                        self.read_char();
+                    }
+                    if self.keep_comments {
+                        Token::COMMENT(comment)
+                    } else {
+                        self.next_token()
                     }
                 } else {
                     Token::SLASH
@@ -159,9 +194,11 @@ impl<'a> Lexer<'a> {
             Some(')') => Token::RPAR,
             Some('{') => Token::LBRA,
             Some('}') => Token::RBRA,
+            Some('[') => Token::LBRACKET,
+            Some(']') => Token::RBRACKET,
 
             Some('>') => {
-                if self.peek_char().unwrap() == &'=' {
+                if self.peek_char() == Some(&'=') {
                     self.read_char();
                     Token::GEQ
                 } else {
@@ -170,7 +207,7 @@ impl<'a> Lexer<'a> {
             }
 
             Some('<') => {
-                if self.peek_char().unwrap() == &'=' {
+                if self.peek_char() == Some(&'=') {
                     self.read_char();
                     Token::LEQ
                 } else {
@@ -179,7 +216,7 @@ impl<'a> Lexer<'a> {
             }
 
             Some('!') => {
-                if self.peek_char().unwrap() == &'=' {
+                if self.peek_char() == Some(&'=') {
                     self.read_char();
                     Token::NEQ
                 } else {
@@ -200,7 +237,10 @@ impl<'a> Lexer<'a> {
                 }
                 // Could be an integer
                 else if char::is_digit(chr, 10) {
-                    Token::INT(self.read_number(chr))
+                    match self.read_number(chr) {
+                        Ok(num) => Token::INT(num),
+                        Err(digits) => Token::ILLEGAL(digits),
+                    }
                 }
                 // Nothing recognized, spit illegal.
                 else {
@@ -210,6 +250,55 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// Returns the tokens of an input in order, each with the range of the source it was read from. EOF is left out.
+    pub fn lex_spans(&mut self) -> Vec<(Token, Range<usize>)> {
+        let mut tokens = Vec::new();
+        loop {
+            match self.next_token() {
+                Token::EOF => break,
+                token => tokens.push((token, self.span())),
+            }
+        }
+        tokens
+    }
+
+    /// Reads every token along with the whitespace and comments before it, in source order. The last token is always EOF, carrying whatever trails the final token.
+    ///
+    /// # Technical Information
+    /// Nothing is dropped, so writing the tokens back out gives the original source. This is what the concrete syntax tree is built from.
+    pub fn lex_lossless(&mut self) -> Vec<SyntaxToken> {
+        self.keep_comments = true;
+        let mut tokens = Vec::new();
+        let mut leading = Vec::new();
+        let mut last = self.pos;
+        loop {
+            let token = self.next_token();
+            let span = self.span();
+            if span.start > last {
+                leading.push(Trivia::Whitespace(String::from(
+                    &self.source[last..span.start],
+                )));
+            }
+            last = span.end;
+            let text = String::from(&self.source[span.clone()]);
+            match token {
+                Token::COMMENT(_) => leading.push(Trivia::Comment(text)),
+                token => {
+                    let done = token == Token::EOF;
+                    tokens.push(SyntaxToken {
+                        leading: std::mem::take(&mut leading),
+                        token,
+                        text,
+                        span,
+                    });
+                    if done {
+                        return tokens;
+                    }
+                }
+            }
+        }
+    }
+
     /// Returns the vector of tokens from an input.
     pub fn lex(&mut self) -> Vec<Token> {
         let mut token_vec: Vec<Token> = Vec::new();
@@ -1,2 +1,3 @@
 //! The lexer module turns input into a vector of tokens.
 pub mod lexer;
+pub mod trivia;
@@ -0,0 +1,43 @@
+//! Tokens that remember the exact text they were read from, along with the whitespace and comments around them.
+use crate::token::token::Token;
+use std::fmt;
+use std::ops::Range;
+
+/// Text the parser ignores, kept so that tools can reproduce the source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trivia {
+    Whitespace(String),
+    /// The whole comment, including /* and */ if it was closed.
+    Comment(String),
+}
+
+impl Trivia {
+    pub fn text(&self) -> &str {
+        match self {
+            Trivia::Whitespace(text) | Trivia::Comment(text) => text,
+        }
+    }
+}
+
+/// A token with the trivia before it attached, so a list of them covers every byte of the source.
+///
+/// # Technical Information
+/// Trivia is always attached to the token that follows it, which leaves whatever is after the last real token on EOF.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxToken {
+    pub leading: Vec<Trivia>,
+    pub token: Token,
+    /// The text of the token exactly as written, i.e `:3` rather than `;`.
+    pub text: String,
+    /// Byte range of the token itself, leaving out its trivia.
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for trivia in self.leading.iter() {
+            f.write_str(trivia.text())?;
+        }
+        f.write_str(&self.text)
+    }
+}
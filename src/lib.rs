@@ -0,0 +1,48 @@
+//! # What is uwucode?
+//! uwucode is an atrocity. In all seriousness, uwucode is a satirical programming language that incorporates egirl slang to make it nearly unreadable. Take for example the following:
+//! ```text
+//! owo hehexd = 1;
+//! uwu nya(lel) {nuzzles (lel==hehexd) {sugoi hehexd;} rawr {sugoi lel*nya(lel-hehexd);};};
+//! nya(5); // -> 120
+//! ```
+//! Of course, one may recognize this is a recursive implementation of the factorial function, however it looks like a mess to anyone else.
+//!
+//! # Embedding
+//! Rust programs can run uwucode through an [`Interpreter`], which keeps its globals between calls.
+
+// Tokens are named after their grammar symbols and each module keeps its logic in a file of the same name.
+#![allow(clippy::upper_case_acronyms, clippy::module_inception)]
+
+// Only the items re-exported below are meant for embedding, the modules are free to change.
+mod analysis;
+mod cli;
+mod eval;
+mod formatter;
+mod interpreter;
+mod lexer;
+mod parser;
+mod repl;
+mod tests;
+mod token;
+mod vm;
+
+pub use crate::eval::context::Context;
+pub use crate::eval::env::Env;
+pub use crate::eval::error::RuntimeError;
+pub use crate::eval::eval::{MapKey, Object, TreeFunction};
+pub use crate::eval::input::{Input, ScriptedInput, StdInput};
+pub use crate::eval::native::{Arity, NativeFunction, NativeRegistry};
+pub use crate::eval::output::{BufferOutput, FileOutput, Output, StdOutput};
+pub use crate::interpreter::error::Error;
+pub use crate::interpreter::interpreter::{Backend, Interpreter};
+pub use crate::parser::error::ParseError;
+// Parse errors name the token that was found instead of the one expected.
+pub use crate::token::token::Token;
+pub use crate::vm::bytecode::CompiledFunction;
+pub use crate::vm::error::LoadError;
+
+/// What the uwucode binary needs from the library, which is not part of the embedding API and may change at any time.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::cli::cli::run;
+}
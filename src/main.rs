@@ -1,33 +1,17 @@
-//! # What is uwucode?
-//! uwucode is an atrocity. In all seriousness, uwucode is a satirical programming language that incorporates egirl slang to make it nearly unreadable. Take for example the following:
-//! ```
-//! owo hehexd = 1;
-//! uwu nya(lel) {nuzzles (lel==hehexd) {sugoi hehexd;} rawr {sugoi lel*nya(lel-hehexd);};};
-//! nya(5); // -> 120
-//! ```
-//! Of course, one may recognize this is a recursive implementation of the factorial function, however it looks like a mess to anyone else.
-
-mod eval;
-mod lexer;
-mod parser;
-mod repl;
-mod tests;
-mod token;
 use std::env;
+use std::process;
+use std::thread;
+use uwucode::__private as cli;
 
-/// uwucode takes in the following arguments:
-/// repl | open
-/// # repl
-/// Opens a REPl to evaluate uwucode. Takes in no additional arguments.
-///
-/// # open
-/// Takes in one argument, which is the filepath.
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    match args[1].as_ref() {
-        "repl" => repl::repl::start(),
-        "open" => repl::interpreter::file_interpret(args[2].as_ref()),
+/// Recursive programs nest many Rust frames per call, so the interpreter runs on a thread with more stack than the main thread gets.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
 
-        val => panic!("{} is not a recognized argument", val),
-    }
+/// uwucode takes in a subcommand, see `uwucode --help`.
+fn main() {
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| cli::run(env::args_os()))
+        .expect("Could not start the interpreter");
+    // The panic message has already been printed, so exit like a panicking main would.
+    process::exit(interpreter.join().unwrap_or(101));
 }
@@ -1,24 +1,40 @@
 //! The abstract syntax tree is built of enums within enums. For instance, a let statement, for example:
-//! ```no_run
+//! ```text
 //! let three = 3;
 //! ```
 //! This would turn into the tree:
-//! ```no_run
+//! ```text
 //! Let(three,Expr(3))
 //! ```
 //!
 //! The complexity of these trees grow, especially when dealing with function calls.
+//!
+//! Every node displays as uwucode source that parses back into the same tree. Parentheses are only added where precedence needs them, and statements are shown without their terminator.
 use crate::token::token::Token;
-
-// TODO: Implement fmt methods for the statements.
+use std::fmt;
 
 /// Statement enums effectively compose the structure of a line of code.
 /// These could be considered the roots of an AST, typically with Expr being the children.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
-    Let { name: String, value: Expr },
-    Define { func_name: String, func: Expr },
-    Return { value: Expr },
+    Let {
+        name: String,
+        value: Expr,
+    },
+    Define {
+        func_name: String,
+        func: Expr,
+    },
+    Return {
+        value: Expr,
+    },
+    IndexAssign {
+        name: String,
+        index: Expr,
+        value: Expr,
+    },
+    Break,
+    Continue,
     Expression(Expr),
 }
 
@@ -29,6 +45,12 @@ pub enum Expr {
     Variable(String),
     Boolean(bool),
     Integer(i64),
+    Array(Vec<Expr>),
+    Map(Vec<(Expr, Expr)>),
+    Index {
+        left: Box<Expr>,
+        index: Box<Expr>,
+    },
     Prefix {
         prefix: Prefix,
         value: Box<Expr>,
@@ -47,6 +69,11 @@ pub enum Expr {
         condition: Box<Expr>,
         instruction: Vec<Statement>,
     },
+    For {
+        variable: String,
+        iterable: Box<Expr>,
+        body: Vec<Statement>,
+    },
     Function {
         parameters: Vec<String>,
         body: Vec<Statement>,
@@ -55,11 +82,6 @@ pub enum Expr {
         function: Box<Expr>, // Function name
         arguments: Vec<Expr>,
     },
-
-    Builtin {
-        function_name: String,
-        arguments: Vec<Expr>,
-    },
 }
 
 #[derive(Debug, Clone, PartialEq, Copy)]
@@ -76,6 +98,7 @@ pub enum Precedence {
     Sum,
     Product,
     Prefix,
+    Index,
 }
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub enum Operator {
@@ -92,10 +115,234 @@ pub enum Operator {
     NotEquals,
 }
 
-pub fn is_builtin(func_name: &str) -> bool {
-    match func_name {
-        "len" | "quwuit" | "dprint" => true,
-        _ => false,
+impl Operator {
+    /// The operator a token stands for between two operands, if any.
+    pub fn from_token(token: &Token) -> Option<Self> {
+        match token {
+            Token::PLUS => Some(Operator::Plus),
+            Token::MINUS => Some(Operator::Minus),
+            Token::SLASH => Some(Operator::Divide),
+            Token::MOD => Some(Operator::Modulo),
+            Token::ASTERISK => Some(Operator::Multiply),
+            Token::LEQ => Some(Operator::LessThanEqual),
+            Token::LE => Some(Operator::LessThan),
+            Token::GEQ => Some(Operator::GreaterThanEqual),
+            Token::GR => Some(Operator::GreaterThan),
+            Token::EQ => Some(Operator::Equals),
+            Token::NEQ => Some(Operator::NotEquals),
+            _ => None,
+        }
+    }
+
+    /// The precedence the parser gives the operator's token.
+    pub fn precedence(&self) -> Precedence {
+        match self {
+            Operator::Plus | Operator::Minus => Precedence::Sum,
+            Operator::Multiply | Operator::Divide | Operator::Modulo => Precedence::Product,
+            Operator::GreaterThan
+            | Operator::GreaterThanEqual
+            | Operator::LessThan
+            | Operator::LessThanEqual => Precedence::LessGreater,
+            Operator::Equals | Operator::NotEquals => Precedence::Equals,
+        }
+    }
+}
+
+impl Expr {
+    /// The precedence the expression binds with, which decides whether it needs parentheses inside of another expression.
+    fn precedence(&self) -> Precedence {
+        match self {
+            Expr::Infix { operator, .. } => operator.precedence(),
+            Expr::Prefix { .. } => Precedence::Prefix,
+            _ => Precedence::Index,
+        }
+    }
+}
+
+/// Shows statements separated by spaces, each with a terminator.
+struct Block<'a>(&'a [Statement]);
+
+impl fmt::Display for Block<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return "{}".fmt(f);
+        }
+        "{ ".fmt(f)?;
+        for statement in self.0 {
+            write!(f, "{}; ", statement)?;
+        }
+        "}".fmt(f)
+    }
+}
+
+/// Shows a subexpression, wrapped in parentheses if it binds less tightly than its position requires.
+struct Operand<'a>(&'a Expr, Precedence);
+
+impl fmt::Display for Operand<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.precedence() < self.1 {
+            write!(f, "({})", self.0)
+        } else {
+            self.0.fmt(f)
+        }
+    }
+}
+
+/// Shows a comma separated list.
+fn fmt_list<T: fmt::Display>(f: &mut fmt::Formatter, items: &[T]) -> fmt::Result {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// Shows a function as uwu, its name if it has one, its parameters and its body. Functions are only written as definitions, so an anonymous one is not valid source.
+pub fn fmt_function(
+    f: &mut fmt::Formatter,
+    name: Option<&str>,
+    parameters: &[String],
+    body: &[Statement],
+) -> fmt::Result {
+    match name {
+        Some(name) => write!(f, "uwu {}(", name)?,
+        None => f.write_str("uwu(")?,
+    }
+    fmt_list(f, parameters)?;
+    write!(f, ") {}", Block(body))
+}
+
+/// Shows a whole program, one statement per line.
+#[cfg(test)]
+pub fn fmt_program(statements: &[Statement]) -> String {
+    statements
+        .iter()
+        .map(|statement| format!("{};\n", statement))
+        .collect()
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Statement::Let { name, value } => write!(f, "owo {} = {}", name, value),
+            Statement::Define {
+                func_name,
+                func: Expr::Function { parameters, body },
+            } => fmt_function(f, Some(func_name), parameters, body),
+            // Only functions can be defined, anything else is shown as the equivalent let.
+            Statement::Define { func_name, func } => write!(f, "owo {} = {}", func_name, func),
+            Statement::Return { value } => write!(f, "sugoi {}", value),
+            Statement::IndexAssign { name, index, value } => {
+                write!(f, "{}[{}] = {}", name, index, value)
+            }
+            Statement::Break => "bweak".fmt(f),
+            Statement::Continue => "continyue".fmt(f),
+            Statement::Expression(expr) => expr.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::String(string) => write!(f, "\"{}\"", string),
+            Expr::Variable(name) => name.fmt(f),
+            Expr::Boolean(true) => "truwu".fmt(f),
+            Expr::Boolean(false) => "fowose".fmt(f),
+            Expr::Integer(num) => num.fmt(f),
+            Expr::Array(elements) => {
+                "[".fmt(f)?;
+                fmt_list(f, elements)?;
+                "]".fmt(f)
+            }
+            Expr::Map(pairs) => {
+                let pairs: Vec<String> = pairs
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect();
+                "{".fmt(f)?;
+                fmt_list(f, &pairs)?;
+                "}".fmt(f)
+            }
+            Expr::Index { left, index } => {
+                write!(f, "{}[{}]", Operand(left, Precedence::Index), index)
+            }
+            Expr::Prefix { prefix, value } => {
+                write!(f, "{}{}", prefix, Operand(value, Precedence::Prefix))
+            }
+            // Operators are left associative, so a right operand of the same precedence needs parentheses.
+            Expr::Infix {
+                left,
+                operator,
+                right,
+            } => {
+                let precedence = operator.precedence();
+                let right = match right.precedence() {
+                    inner if inner == precedence => format!("({})", right),
+                    _ => Operand(right, precedence).to_string(),
+                };
+                write!(f, "{} {} {}", Operand(left, precedence), operator, right)
+            }
+            Expr::If {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                write!(f, "nuzzles ({}) {}", condition, Block(consequence))?;
+                if !alternative.is_empty() {
+                    write!(f, " rawr {}", Block(alternative))?;
+                }
+                Ok(())
+            }
+            Expr::While {
+                condition,
+                instruction,
+            } => write!(f, "nyaa ({}) {}", condition, Block(instruction)),
+            Expr::For {
+                variable,
+                iterable,
+                body,
+            } => write!(f, "fur ({} in {}) {}", variable, iterable, Block(body)),
+            Expr::Function { parameters, body } => fmt_function(f, None, parameters, body),
+            Expr::Call {
+                function,
+                arguments,
+            } => {
+                write!(f, "{}(", Operand(function, Precedence::Index))?;
+                fmt_list(f, arguments)?;
+                ")".fmt(f)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Prefix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Prefix::Bang => "!".fmt(f),
+            Prefix::Minus => "-".fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            Operator::Plus => "+",
+            Operator::Minus => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Modulo => "%",
+            Operator::GreaterThan => ">",
+            Operator::GreaterThanEqual => ">=",
+            Operator::LessThan => "<",
+            Operator::LessThanEqual => "<=",
+            Operator::Equals => "==",
+            Operator::NotEquals => "!=",
+        };
+        symbol.fmt(f)
     }
 }
 
@@ -114,6 +361,7 @@ impl Token {
             Token::GR => Precedence::LessGreater,
             Token::EQ => Precedence::Equals,
             Token::NEQ => Precedence::Equals,
+            Token::LBRACKET => Precedence::Index,
             _ => Precedence::Lowest,
         }
     }
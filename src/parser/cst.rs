@@ -0,0 +1,320 @@
+//! The concrete syntax tree groups the lossless tokens of a program by the grammar, so unlike the AST it keeps every byte of the source, comments and whitespace included.
+//! ```text
+//! owo x = 1 :3 /* one */
+//! ```
+//! This would turn into the tree:
+//! ```text
+//! Program
+//!   Let
+//!     LET "owo", IDENT "x", ASSIGN "=", Literal(INT "1"), SEMICOLON ":3"
+//!   EOF, with " /* one */" before it
+//! ```
+//!
+//! Tools can walk the tree to find where things are written, and lower any node into the matching AST node.
+use crate::lexer::lexer::Lexer;
+use crate::lexer::trivia::SyntaxToken;
+use crate::parser::ast::{Expr, Operator, Prefix, Statement};
+use crate::parser::error::ParseError;
+use crate::parser::parser::parse_tokens;
+use crate::token::token::Token;
+use std::fmt;
+use std::ops::Range;
+
+/// What a node of the tree stands for. Statement nodes end with their terminator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyntaxKind {
+    Program,
+    Let,
+    Define,
+    Return,
+    IndexAssign,
+    Break,
+    Continue,
+    ExpressionStatement,
+    ParameterList,
+    ArgumentList,
+    Block,
+    MapEntry,
+    Literal,
+    Name,
+    /// An expression in parentheses.
+    Group,
+    Array,
+    Map,
+    Prefix,
+    Infix,
+    Index,
+    Call,
+    If,
+    While,
+    For,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+/// A node of the tree, its children hold every token it was parsed from in source order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxNode {
+    kind: SyntaxKind,
+    children: Vec<SyntaxElement>,
+}
+
+impl From<SyntaxNode> for SyntaxElement {
+    fn from(node: SyntaxNode) -> Self {
+        SyntaxElement::Node(node)
+    }
+}
+
+impl From<SyntaxToken> for SyntaxElement {
+    fn from(token: SyntaxToken) -> Self {
+        SyntaxElement::Token(token)
+    }
+}
+
+impl SyntaxNode {
+    pub(crate) fn new(kind: SyntaxKind, children: Vec<SyntaxElement>) -> Self {
+        SyntaxNode { kind, children }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn children(&self) -> &[SyntaxElement] {
+        &self.children
+    }
+
+    /// The nodes directly below this one.
+    pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// Every token under the node, in source order.
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = vec![];
+        for child in self.children.iter() {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    /// The byte range from the node's first token to its last, leaving out the trivia before it.
+    pub fn span(&self) -> Range<usize> {
+        let tokens = self.tokens();
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => first.span.start..last.span.end,
+            _ => 0..0,
+        }
+    }
+
+    /// Every node in the subtree, starting with this one and in source order.
+    pub fn descendants(&self) -> Vec<&SyntaxNode> {
+        let mut nodes = vec![self];
+        for node in self.nodes() {
+            nodes.extend(node.descendants());
+        }
+        nodes
+    }
+
+    /// The identifiers directly below this one, i.e the name of a definition or the parameters in a parameter list.
+    pub fn names(&self) -> impl Iterator<Item = &SyntaxToken> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Token(token) if matches!(token.token, Token::IDENT(_)) => Some(token),
+            _ => None,
+        })
+    }
+
+    /// The tokens directly below this one.
+    fn own_tokens(&self) -> impl Iterator<Item = &Token> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Token(token) => Some(&token.token),
+            SyntaxElement::Node(_) => None,
+        })
+    }
+
+    fn ident(&self) -> Option<String> {
+        self.own_tokens().find_map(|token| match token {
+            Token::IDENT(name) => Some(name.clone()),
+            _ => None,
+        })
+    }
+
+    fn nth_expr(&self, n: usize) -> Option<Expr> {
+        self.nodes().nth(n)?.to_expr()
+    }
+
+    /// The statements of a program or block.
+    fn block(&self) -> Option<Vec<Statement>> {
+        match self.kind {
+            SyntaxKind::Program | SyntaxKind::Block => {
+                self.nodes().map(SyntaxNode::to_statement).collect()
+            }
+            _ => None,
+        }
+    }
+
+    /// Lowers a program into the statements the parser would give for it.
+    pub fn to_program(&self) -> Option<Vec<Statement>> {
+        match self.kind {
+            SyntaxKind::Program => self.block(),
+            _ => None,
+        }
+    }
+
+    /// Lowers a statement node into the matching AST statement, or returns None for any other kind of node.
+    pub fn to_statement(&self) -> Option<Statement> {
+        let statement = match self.kind {
+            SyntaxKind::Let => Statement::Let {
+                name: self.ident()?,
+                value: self.nth_expr(0)?,
+            },
+            SyntaxKind::Define => {
+                let mut nodes = self.nodes();
+                let parameters = nodes.next()?;
+                Statement::Define {
+                    func_name: self.ident()?,
+                    func: Expr::Function {
+                        parameters: parameters
+                            .own_tokens()
+                            .filter_map(|token| match token {
+                                Token::IDENT(name) => Some(name.clone()),
+                                _ => None,
+                            })
+                            .collect(),
+                        body: nodes.next()?.block()?,
+                    },
+                }
+            }
+            SyntaxKind::Return => Statement::Return {
+                value: self.nth_expr(0)?,
+            },
+            SyntaxKind::IndexAssign => {
+                let target = self.nodes().next()?;
+                Statement::IndexAssign {
+                    name: target.nodes().next()?.ident()?,
+                    index: target.nth_expr(1)?,
+                    value: self.nth_expr(1)?,
+                }
+            }
+            SyntaxKind::Break => Statement::Break,
+            SyntaxKind::Continue => Statement::Continue,
+            SyntaxKind::ExpressionStatement => Statement::Expression(self.nth_expr(0)?),
+            _ => return None,
+        };
+        Some(statement)
+    }
+
+    /// Lowers an expression node into the matching AST expression, or returns None for any other kind of node.
+    pub fn to_expr(&self) -> Option<Expr> {
+        let expr = match self.kind {
+            SyntaxKind::Literal => match self.own_tokens().next()? {
+                Token::INT(value) => Expr::Integer(*value),
+                Token::STRING(value) => Expr::String(value.clone()),
+                Token::TRUE => Expr::Boolean(true),
+                Token::FALSE => Expr::Boolean(false),
+                _ => return None,
+            },
+            SyntaxKind::Name => Expr::Variable(self.ident()?),
+            SyntaxKind::Group => self.nth_expr(0)?,
+            SyntaxKind::Array => Expr::Array(
+                self.nodes()
+                    .map(SyntaxNode::to_expr)
+                    .collect::<Option<_>>()?,
+            ),
+            SyntaxKind::Map => Expr::Map(
+                self.nodes()
+                    .map(|entry| Some((entry.nth_expr(0)?, entry.nth_expr(1)?)))
+                    .collect::<Option<_>>()?,
+            ),
+            SyntaxKind::Prefix => Expr::Prefix {
+                prefix: match self.own_tokens().next()? {
+                    Token::BANG => Prefix::Bang,
+                    _ => Prefix::Minus,
+                },
+                value: Box::new(self.nth_expr(0)?),
+            },
+            SyntaxKind::Infix => Expr::Infix {
+                left: Box::new(self.nth_expr(0)?),
+                operator: Operator::from_token(self.own_tokens().next()?)?,
+                right: Box::new(self.nth_expr(1)?),
+            },
+            SyntaxKind::Index => Expr::Index {
+                left: Box::new(self.nth_expr(0)?),
+                index: Box::new(self.nth_expr(1)?),
+            },
+            SyntaxKind::Call => {
+                let mut nodes = self.nodes();
+                Expr::Call {
+                    function: Box::new(nodes.next()?.to_expr()?),
+                    arguments: nodes
+                        .next()?
+                        .nodes()
+                        .map(SyntaxNode::to_expr)
+                        .collect::<Option<_>>()?,
+                }
+            }
+            SyntaxKind::If => {
+                let mut nodes = self.nodes();
+                Expr::If {
+                    condition: Box::new(nodes.next()?.to_expr()?),
+                    consequence: nodes.next()?.block()?,
+                    alternative: match nodes.next() {
+                        Some(alternative) => alternative.block()?,
+                        None => vec![],
+                    },
+                }
+            }
+            SyntaxKind::While => Expr::While {
+                condition: Box::new(self.nth_expr(0)?),
+                instruction: self.nodes().nth(1)?.block()?,
+            },
+            SyntaxKind::For => Expr::For {
+                variable: self.ident()?,
+                iterable: Box::new(self.nth_expr(0)?),
+                body: self.nodes().nth(1)?.block()?,
+            },
+            _ => return None,
+        };
+        Some(expr)
+    }
+}
+
+/// Writes out every token with its trivia, which gives back the source the tree was parsed from.
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for token in self.tokens() {
+            token.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses source into a concrete syntax tree whose root is a Program node.
+///
+/// # Examples
+/// ```ignore
+/// let source = "owo x = 1 :3 /* one */";
+/// let tree = parse_cst(source).unwrap();
+/// assert_eq!(tree.to_string(), source);
+/// ```
+pub fn parse_cst(source: &str) -> Result<SyntaxNode, ParseError> {
+    Ok(parse_source(source)?.0)
+}
+
+/// Parses source into a concrete syntax tree along with the statements it lowers to, for tools that need both.
+pub fn parse_source(source: &str) -> Result<(SyntaxNode, Vec<Statement>), ParseError> {
+    let mut input = Lexer::new(source).lex_lossless();
+    // Reversed so tokens can be popped off in order, like the lexer gives them to the parser.
+    input.reverse();
+    parse_tokens(&mut input)
+}
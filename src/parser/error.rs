@@ -0,0 +1,60 @@
+//! Errors raised while parsing tokens into statements.
+use crate::token::token::Token;
+use std::error;
+use std::fmt;
+
+/// Parse errors describe the first problem found, after which parsing stops.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParseError {
+    /// A token other than the one expected was found. Finding EOF means the input ended early.
+    UnexpectedToken { expected: String, found: Token },
+    /// The lexer could not make sense of some text, i.e an unknown symbol or an integer too large to fit.
+    IllegalToken(String),
+    /// bweak or continyue was used outside of a loop body.
+    LoopControlOutsideLoop,
+    /// Something other than an indexed variable was found on the left of =.
+    InvalidAssignment,
+}
+
+impl ParseError {
+    /// Creates an unexpected token error, treating a missing token as the end of input. Illegal tokens are reported as such, whatever was expected.
+    pub(crate) fn unexpected(expected: &str, found: Option<Token>) -> Self {
+        match found {
+            Some(Token::ILLEGAL(text)) => ParseError::IllegalToken(text),
+            found => ParseError::UnexpectedToken {
+                expected: String::from(expected),
+                found: found.unwrap_or(Token::EOF),
+            },
+        }
+    }
+
+    /// Checks whether the input ended before the statement was complete, in which case more input could fix it.
+    pub fn is_incomplete(&self) -> bool {
+        matches!(
+            self,
+            ParseError::UnexpectedToken {
+                found: Token::EOF,
+                ..
+            }
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedToken { expected, found } => {
+                write!(f, "expected {}, instead found {:?}", expected, found)
+            }
+            ParseError::IllegalToken(text) => write!(f, "{} is not valid uwucode", text),
+            ParseError::LoopControlOutsideLoop => {
+                "bweak and continyue may only be used inside of a loop".fmt(f)
+            }
+            ParseError::InvalidAssignment => {
+                "only variables indexed with [] can be assigned to with =".fmt(f)
+            }
+        }
+    }
+}
+
+impl error::Error for ParseError {}
@@ -2,4 +2,6 @@
 //!
 
 pub mod ast;
+pub mod cst;
+pub mod error;
 pub mod parser;
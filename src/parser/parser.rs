@@ -1,5 +1,10 @@
 //! Handles the parsing of tokens that come from the lexer.
-use crate::parser::ast::{is_builtin, Expr, Operator, Precedence, Prefix, Statement};
+//!
+//! The parser builds the concrete syntax tree, which keeps every token it was made from, and the statements of the AST are lowered from that tree. This way the tools that need to know where things are written and the evaluator share a single grammar.
+use crate::lexer::trivia::SyntaxToken;
+use crate::parser::ast::{Expr, Operator, Precedence, Statement};
+use crate::parser::cst::{SyntaxElement, SyntaxKind, SyntaxNode};
+use crate::parser::error::ParseError;
 use crate::token::token::Token;
 
 /// The parse function turns a vector of tokens into a vector of statements. This is done by grouping them into one of several categories.
@@ -9,69 +14,246 @@ use crate::token::token::Token;
 /// - Function (define)
 /// - Right brace (end body)
 /// - Return (return statement)
+/// - Break and continue (loop control)
 /// - EOF (end of file)
 /// - Expressions (everything else)
-pub fn parse(input: &mut Vec<Token>) -> Vec<Statement> {
+///
+/// Once parsed, the statements are checked to ensure bweak and continyue only appear inside of loops.
+pub fn parse(input: &mut Vec<Token>) -> Result<Vec<Statement>, ParseError> {
+    // Tokens without their source text parse like any other, they just can't be written back out.
+    let mut tokens = input
+        .drain(..)
+        .map(|token| SyntaxToken {
+            leading: vec![],
+            token,
+            text: String::new(),
+            span: 0..0,
+        })
+        .collect();
+    let (_, statements) = parse_tokens(&mut tokens)?;
+    Ok(statements)
+}
+
+/// Parses lossless tokens, given in reverse like the lexer returns them, into a syntax tree whose root is a Program node, along with the statements it lowers to.
+pub fn parse_tokens(
+    input: &mut Vec<SyntaxToken>,
+) -> Result<(SyntaxNode, Vec<Statement>), ParseError> {
+    let mut children = parse_block(input)?;
+    // parse_block also stops at a right brace, which has nothing to close at the top level.
+    children.push(expect(input, Token::EOF, "a statement")?.into());
+    let tree = SyntaxNode::new(SyntaxKind::Program, children);
+
+    // Every node is built with all of its parts, so the tree always lowers.
+    let statements = tree
+        .to_program()
+        .expect("the parser only builds complete nodes");
+    check_loop_control(&statements, false)?;
+    Ok((tree, statements))
+}
+
+fn peek(input: &[SyntaxToken]) -> Option<&Token> {
+    input.last().map(|token| &token.token)
+}
+
+/// Pops the next token, which the caller has already peeked at.
+fn bump(input: &mut Vec<SyntaxToken>) -> SyntaxToken {
+    input.pop().expect("the token was peeked at")
+}
+
+/// Builds the error for finding some token, or the end of input, instead of the expected one.
+fn unexpected(expected: &str, found: Option<SyntaxToken>) -> ParseError {
+    ParseError::unexpected(expected, found.map(|found| found.token))
+}
+
+/// Pops the next token, failing if it is not the expected one.
+fn expect(
+    input: &mut Vec<SyntaxToken>,
+    token: Token,
+    description: &str,
+) -> Result<SyntaxToken, ParseError> {
+    match input.pop() {
+        Some(found) if found.token == token => Ok(found),
+        found => Err(unexpected(description, found)),
+    }
+}
+
+/// Pops the next token, failing if it is not an identifier.
+fn expect_ident(
+    input: &mut Vec<SyntaxToken>,
+    description: &str,
+) -> Result<SyntaxToken, ParseError> {
+    match input.pop() {
+        Some(found) if matches!(found.token, Token::IDENT(_)) => Ok(found),
+        found => Err(unexpected(description, found)),
+    }
+}
+
+/// Parses statements until the end of the input or an enclosing right brace.
+fn parse_block(input: &mut Vec<SyntaxToken>) -> Result<Vec<SyntaxElement>, ParseError> {
     let mut statements = vec![];
 
     // Process each statement here
     loop {
-        let top_token = match input.last() {
-            Some(token) => token,
-            None => panic!("Vec<Token> list found empty."),
-        };
-
-        match top_token {
-            Token::EOF => break, // We've reached the end of line or file
-            Token::LET => parse_let(input, &mut statements), // Define a variable
-            Token::FUNCTION => parse_function(input, &mut statements), // Define a function
-            Token::RBRA => break, // We've reached the end of an enclosing
-            Token::RETURN => parse_return(input, &mut statements), // We've hit a return statement
-            _ => statements.push(Statement::Expression(parse_expression(
-                input,
-                Precedence::Lowest,
-            ))), // Deal with an expression
+        match peek(input) {
+            None => return Err(unexpected("a statement", None)),
+            Some(Token::EOF) | Some(Token::RBRA) => break, // We've reached the end of the file or of an enclosing
+            Some(_) => statements.push(parse_statement(input)?.into()),
         }
+    }
+
+    Ok(statements)
+}
+
+/// Parses a block along with the braces around it.
+fn parse_braced_block(input: &mut Vec<SyntaxToken>) -> Result<SyntaxNode, ParseError> {
+    let mut children = vec![expect(input, Token::LBRA, "{")?.into()];
+    children.extend(parse_block(input)?);
+    children.push(expect(input, Token::RBRA, "}")?.into());
+    Ok(SyntaxNode::new(SyntaxKind::Block, children))
+}
+
+/// Parses a single statement along with its terminator.
+fn parse_statement(input: &mut Vec<SyntaxToken>) -> Result<SyntaxNode, ParseError> {
+    let (kind, mut children) = match peek(input) {
+        Some(Token::LET) => (SyntaxKind::Let, parse_let(input)?), // Define a variable
+        Some(Token::FUNCTION) => (SyntaxKind::Define, parse_function(input)?), // Define a function
+        Some(Token::RETURN) => (
+            // We've hit a return statement
+            SyntaxKind::Return,
+            vec![
+                bump(input).into(),
+                parse_expression(input, Precedence::Lowest)?.into(),
+            ],
+        ),
+        Some(Token::BREAK) => (SyntaxKind::Break, vec![bump(input).into()]),
+        Some(Token::CONTINUE) => (SyntaxKind::Continue, vec![bump(input).into()]),
+        _ => parse_expression_statement(input)?, // Deal with an expression
+    };
+
+    // Since the subcalls modify the vector of tokens, we should reach a semicolon at the end.
+    children.push(expect(input, Token::SEMICOLON, ":3 or ;")?.into());
+    Ok(SyntaxNode::new(kind, children))
+}
 
-        // Since the subcalls modify the vector of tokens, we should reach a semicolon at the end.
-        assert_eq!(input.pop(), Some(Token::SEMICOLON));
+/// Fails if bweak or continyue is found outside of a loop body. Function bodies are not part of an enclosing loop.
+fn check_loop_control(statements: &[Statement], in_loop: bool) -> Result<(), ParseError> {
+    for statement in statements {
+        match statement {
+            Statement::Break | Statement::Continue if !in_loop => {
+                return Err(ParseError::LoopControlOutsideLoop)
+            }
+            Statement::Break | Statement::Continue => (),
+            Statement::Let { value, .. } | Statement::Return { value } => {
+                check_loop_control_expr(value, in_loop)?
+            }
+            Statement::Define { func, .. } => check_loop_control_expr(func, in_loop)?,
+            Statement::IndexAssign { index, value, .. } => {
+                check_loop_control_expr(index, in_loop)?;
+                check_loop_control_expr(value, in_loop)?;
+            }
+            Statement::Expression(expr) => check_loop_control_expr(expr, in_loop)?,
+        }
     }
+    Ok(())
+}
 
-    return statements;
+/// Searches an expression for bodies of statements to check for loop control.
+fn check_loop_control_expr(expr: &Expr, in_loop: bool) -> Result<(), ParseError> {
+    match expr {
+        Expr::String(_) | Expr::Variable(_) | Expr::Boolean(_) | Expr::Integer(_) => Ok(()),
+        Expr::Array(elements) => elements
+            .iter()
+            .try_for_each(|element| check_loop_control_expr(element, in_loop)),
+        Expr::Map(pairs) => pairs.iter().try_for_each(|(key, value)| {
+            check_loop_control_expr(key, in_loop)?;
+            check_loop_control_expr(value, in_loop)
+        }),
+        Expr::Index { left, index } => {
+            check_loop_control_expr(left, in_loop)?;
+            check_loop_control_expr(index, in_loop)
+        }
+        Expr::Prefix { value, .. } => check_loop_control_expr(value, in_loop),
+        Expr::Infix { left, right, .. } => {
+            check_loop_control_expr(left, in_loop)?;
+            check_loop_control_expr(right, in_loop)
+        }
+        Expr::If {
+            condition,
+            consequence,
+            alternative,
+        } => {
+            check_loop_control_expr(condition, in_loop)?;
+            check_loop_control(consequence, in_loop)?;
+            check_loop_control(alternative, in_loop)
+        }
+        Expr::While {
+            condition,
+            instruction,
+        } => {
+            check_loop_control_expr(condition, in_loop)?;
+            check_loop_control(instruction, true)
+        }
+        Expr::For { iterable, body, .. } => {
+            check_loop_control_expr(iterable, in_loop)?;
+            check_loop_control(body, true)
+        }
+        Expr::Function { body, .. } => check_loop_control(body, false),
+        Expr::Call {
+            function,
+            arguments,
+        } => {
+            check_loop_control_expr(function, in_loop)?;
+            arguments
+                .iter()
+                .try_for_each(|arg| check_loop_control_expr(arg, in_loop))
+        }
+    }
 }
 
 /// Parses let statement by resolving an identifier name and an expression.
 ///
 /// # Technical information
 /// The function effectively transforms a line with a let statement into a let expression.
-fn parse_let(input: &mut Vec<Token>, statements: &mut Vec<Statement>) {
-    assert_eq!(input.pop(), Some(Token::LET)); // Sanity check
-
-    // wtfs going on here lol, double enum extraction
-    let var_name: String = match input.pop() {
-        Some(Token::IDENT(var)) => var,
-        _ => panic!("String type not found for variable name."),
-    };
-
-    // We're at = stage
-    assert_eq!(input.pop(), Some(Token::ASSIGN));
-    // Now we're at the expression eval stage, leave it to parse expr
-    let value = parse_expression(input, Precedence::Lowest);
-    statements.push(Statement::Let {
-        name: var_name,
-        value,
-    });
+fn parse_let(input: &mut Vec<SyntaxToken>) -> Result<Vec<SyntaxElement>, ParseError> {
+    Ok(vec![
+        bump(input).into(),
+        expect_ident(input, "a variable name")?.into(),
+        // We're at = stage
+        expect(input, Token::ASSIGN, "=")?.into(),
+        // Now we're at the expression eval stage, leave it to parse expr
+        parse_expression(input, Precedence::Lowest)?.into(),
+    ])
 }
 
-fn parse_return(input: &mut Vec<Token>, statements: &mut Vec<Statement>) {
-    /*
-    Let's have a look at our Return enum now. Return {value:Expr} which means
-    we need to just parse the expression.
-    */
-    assert_eq!(input.pop(), Some(Token::RETURN));
-    // Now evaluate the expression
-    let value = parse_expression(input, Precedence::Lowest);
-    statements.push(Statement::Return { value });
+/// Parses an expression, which may turn out to be the target of an index assignment.
+///
+/// # Technical Information
+/// An expression such as `map["key"]` followed by `=` becomes an insertion, i.e `map["key"] = value`.
+fn parse_expression_statement(
+    input: &mut Vec<SyntaxToken>,
+) -> Result<(SyntaxKind, Vec<SyntaxElement>), ParseError> {
+    let expr = parse_expression(input, Precedence::Lowest)?;
+
+    if peek(input) != Some(&Token::ASSIGN) {
+        return Ok((SyntaxKind::ExpressionStatement, vec![expr.into()]));
+    }
+
+    let is_indexed_variable = expr.kind() == SyntaxKind::Index
+        && expr
+            .nodes()
+            .next()
+            .is_some_and(|left| left.kind() == SyntaxKind::Name);
+    if !is_indexed_variable {
+        return Err(ParseError::InvalidAssignment);
+    }
+    Ok((
+        SyntaxKind::IndexAssign,
+        vec![
+            expr.into(),
+            bump(input).into(),
+            parse_expression(input, Precedence::Lowest)?.into(),
+        ],
+    ))
 }
 
 /// Parses a function definition, which consists of statements from the other categories.
@@ -83,206 +265,266 @@ fn parse_return(input: &mut Vec<Token>, statements: &mut Vec<Statement>) {
 /// }
 /// This means that each argument and the body have to be individually parsed.
 ///
-fn parse_function(input: &mut Vec<Token>, statements: &mut Vec<Statement>) {
-    assert_eq!(input.pop(), Some(Token::FUNCTION));
-
+fn parse_function(input: &mut Vec<SyntaxToken>) -> Result<Vec<SyntaxElement>, ParseError> {
+    let keyword = bump(input);
     // Next thing is the function name, add it in
-    let func_name: String = match input.pop() {
-        Some(Token::IDENT(name)) => name,
-        a => panic!(
-            "String not found for the function name, instead found {:?}",
-            a
-        ),
-    };
+    let name = expect_ident(input, "a function name")?;
 
     // Now we're at args, first thing is the LPAR
-    assert_eq!(input.pop(), Some(Token::LPAR));
+    let mut parameters = vec![expect(input, Token::LPAR, "(")?.into()];
 
     // Read arguments
-    let mut parameters = vec![];
-
     loop {
         match input.pop() {
-            Some(Token::RPAR) => break,
-            Some(Token::IDENT(var)) => {
-                // Find an arg, add then proceed
-                parameters.push(var); // push to vec list
-                                      // Either separate the argument, finish reading or panic.
+            Some(close) if close.token == Token::RPAR => {
+                parameters.push(close.into());
+                break;
+            }
+            Some(parameter) if matches!(parameter.token, Token::IDENT(_)) => {
+                parameters.push(parameter.into());
+                // Either separate the argument, finish reading or fail.
                 match input.pop() {
-                    Some(Token::RPAR) => break,
-                    Some(Token::COMMA) => continue,
-                    _ => panic!("Object after parameter was not comma or bracket"),
-                };
+                    Some(close) if close.token == Token::RPAR => {
+                        parameters.push(close.into());
+                        break;
+                    }
+                    Some(comma) if comma.token == Token::COMMA => parameters.push(comma.into()),
+                    found => return Err(unexpected(", or )", found)),
+                }
             }
-            _ => panic!("Object after param was not comma or bracket"),
+            found => return Err(unexpected("a parameter or )", found)),
         }
     }
 
-    // Parse the body
-    assert_eq!(input.pop(), Some(Token::LBRA)); // {
-    let body = parse(input); // will return code of inside
-    assert_eq!(input.pop(), Some(Token::RBRA)); // }
+    Ok(vec![
+        keyword.into(),
+        name.into(),
+        SyntaxNode::new(SyntaxKind::ParameterList, parameters).into(),
+        // Parse the body
+        parse_braced_block(input)?.into(),
+    ])
+}
+
+/// Parses comma separated items up to a closing token, assuming the opening token has been consumed. A trailing comma is allowed.
+fn parse_list<F>(
+    input: &mut Vec<SyntaxToken>,
+    kind: SyntaxKind,
+    open: SyntaxToken,
+    close: Token,
+    separator: &str,
+    mut item: F,
+) -> Result<SyntaxNode, ParseError>
+where
+    F: FnMut(&mut Vec<SyntaxToken>) -> Result<SyntaxNode, ParseError>,
+{
+    let mut children = vec![open.into()];
+
+    loop {
+        if peek(input) == Some(&close) {
+            children.push(bump(input).into());
+            break;
+        }
+        children.push(item(input)?.into());
+
+        match input.pop() {
+            Some(found) if found.token == close => {
+                children.push(found.into());
+                break;
+            }
+            Some(comma) if comma.token == Token::COMMA => children.push(comma.into()),
+            found => return Err(unexpected(separator, found)),
+        }
+    }
+    Ok(SyntaxNode::new(kind, children))
+}
 
-    statements.push(Statement::Define {
-        func_name,
-        func: Expr::Function { parameters, body },
-    });
+/// Parses an expression of the lowest precedence, i.e an element of a list.
+fn parse_lowest(input: &mut Vec<SyntaxToken>) -> Result<SyntaxNode, ParseError> {
+    parse_expression(input, Precedence::Lowest)
 }
 
-/// Parses most expressions that involve primitives, operators or basic conditionals.
+/// Parses a key-value pair of a map literal.
 ///
 /// # Technical Information
-///
-fn parse_expression(input: &mut Vec<Token>, precedence: Precedence) -> Expr {
-    let mut left_expr = match input.pop() {
-        Some(val) => {
-            match val {
-                // This is a Token type
-                // Primitives
-                Token::INT(value) => Expr::Integer(value),
-                Token::TRUE => Expr::Boolean(true),
-                Token::FALSE => Expr::Boolean(false),
-                Token::IDENT(value) => {
-                    // for implementing builtin, do a logic check here
-                    if input.last() == Some(&Token::LPAR) {
-                        input.pop();
-                        let mut args = vec![];
-
-                        loop {
-                            match input.last() {
-                                Some(Token::RPAR) => {
-                                    input.pop();
-                                    break;
-                                }
-                                None => panic!("weird stuff happened"),
-                                _ => args.push(parse_expression(input, Precedence::Lowest)),
-                            }
-
-                            match input.pop() {
-                                Some(Token::RPAR) => break,
-                                Some(Token::COMMA) => continue,
-                                _ => panic!("Unexpected parameter"),
-                            }
-                        }
-                        if is_builtin(&value as &str) {
-                            Expr::Builtin {
-                                function_name: value,
-                                arguments: args,
-                            }
-                        } else {
-                            Expr::Call {
-                                function: Box::new(Expr::Variable(value)),
-                                arguments: args,
-                            }
-                        }
-                    } else {
-                        Expr::Variable(value)
-                    }
-                }
-                Token::STRING(value) => Expr::String(value),
-
-                // Prefix types [A B]
-                Token::BANG => Expr::Prefix {
-                    prefix: Prefix::Bang,
-                    value: Box::new(parse_expression(input, Precedence::Prefix)),
-                },
-
-                Token::MINUS => Expr::Prefix {
-                    prefix: Prefix::Minus,
-                    value: Box::new(parse_expression(input, Precedence::Prefix)),
-                },
-
-                // conditional
-                Token::IF => {
-                    assert_eq!(Some(Token::LPAR), input.pop());
-                    let condition = parse_expression(input, Precedence::Lowest);
-                    assert_eq!(Some(Token::RPAR), input.pop());
-
-                    // Parse body
-                    assert_eq!(Some(Token::LBRA), input.pop());
-                    let consequence = parse(input);
-                    assert_eq!(Some(Token::RBRA), input.pop());
-
-                    let alternative = if input.last() == Some(&Token::ELSE) {
-                        // ELSE CONDITION
-                        input.pop();
-                        assert_eq!(Some(Token::LBRA), input.pop());
-                        let alternative = parse(input);
-                        assert_eq!(Some(Token::RBRA), input.pop());
-                        alternative
-                    } else {
-                        // No alternative
-                        Vec::new()
-                    };
-
-                    Expr::If {
-                        condition: Box::new(condition),
-                        consequence,
-                        alternative,
-                    }
-                }
+/// Braces otherwise only open the bodies of nuzzles, rawr, nyaa and uwu, which consume their own braces, so a brace in expression position always starts a map.
+/// Note that `:3` always ends a statement, so `{"a":3}` must be written as `{"a": 3}`.
+fn parse_map_entry(input: &mut Vec<SyntaxToken>) -> Result<SyntaxNode, ParseError> {
+    let key = parse_lowest(input)?;
+    let colon = match input.pop() {
+        Some(colon) if colon.token == Token::COLON => colon,
+        found @ Some(SyntaxToken {
+            token: Token::SEMICOLON,
+            ..
+        }) => {
+            return Err(unexpected(
+                ": after map key (:3 ends a statement, so write : 3 instead)",
+                found,
+            ))
+        }
+        found => return Err(unexpected(": after map key", found)),
+    };
+    Ok(SyntaxNode::new(
+        SyntaxKind::MapEntry,
+        vec![key.into(), colon.into(), parse_lowest(input)?.into()],
+    ))
+}
 
-                // while, control flow 1
-                Token::WHILE => {
-                    // While (THING) { do thing}
-                    assert_eq!(Some(Token::LPAR), input.pop());
-                    let condition = parse_expression(input, Precedence::Lowest);
-                    assert_eq!(Some(Token::RPAR), input.pop());
-
-                    // Parse body
-                    assert_eq!(Some(Token::LBRA), input.pop());
-                    let instruction = parse(input);
-                    assert_eq!(Some(Token::RBRA), input.pop());
-
-                    Expr::While {
-                        condition: Box::new(condition),
-                        instruction,
-                    }
-                }
+/// Parses the condition of nuzzles or nyaa along with its parentheses.
+fn parse_condition(
+    input: &mut Vec<SyntaxToken>,
+    children: &mut Vec<SyntaxElement>,
+) -> Result<(), ParseError> {
+    children.push(expect(input, Token::LPAR, "(")?.into());
+    children.push(parse_lowest(input)?.into());
+    children.push(expect(input, Token::RPAR, ")")?.into());
+    Ok(())
+}
+
+/// Parses most expressions that involve primitives, operators or basic conditionals.
+fn parse_expression(
+    input: &mut Vec<SyntaxToken>,
+    precedence: Precedence,
+) -> Result<SyntaxNode, ParseError> {
+    let first = match input.pop() {
+        Some(first) => first,
+        None => return Err(unexpected("an expression", None)),
+    };
+    let mut left = match first.token {
+        // Primitives
+        Token::INT(_) | Token::STRING(_) | Token::TRUE | Token::FALSE => {
+            SyntaxNode::new(SyntaxKind::Literal, vec![first.into()])
+        }
+        Token::IDENT(_) => {
+            let name = SyntaxNode::new(SyntaxKind::Name, vec![first.into()]);
+            // Builtins are looked up at runtime, so they are parsed like any other call
+            if peek(input) == Some(&Token::LPAR) {
+                let open = bump(input);
+                let arguments = parse_list(
+                    input,
+                    SyntaxKind::ArgumentList,
+                    open,
+                    Token::RPAR,
+                    ", or )",
+                    parse_lowest,
+                )?;
+                SyntaxNode::new(SyntaxKind::Call, vec![name.into(), arguments.into()])
+            } else {
+                name
+            }
+        }
+
+        // Grouping, the parentheses only change the order of evaluation
+        Token::LPAR => SyntaxNode::new(
+            SyntaxKind::Group,
+            vec![
+                first.into(),
+                parse_lowest(input)?.into(),
+                expect(input, Token::RPAR, ")")?.into(),
+            ],
+        ),
+
+        // Collections
+        Token::LBRACKET => parse_list(
+            input,
+            SyntaxKind::Array,
+            first,
+            Token::RBRACKET,
+            ", or ] in array literal",
+            parse_lowest,
+        )?,
+        Token::LBRA => parse_list(
+            input,
+            SyntaxKind::Map,
+            first,
+            Token::RBRA,
+            ", or } in map literal",
+            parse_map_entry,
+        )?,
+
+        // Prefix types [A B]
+        Token::BANG | Token::MINUS => SyntaxNode::new(
+            SyntaxKind::Prefix,
+            vec![
+                first.into(),
+                parse_expression(input, Precedence::Prefix)?.into(),
+            ],
+        ),
 
-                // Error
-                _ => panic!("Parser error: Not recognized!"),
+        // conditional
+        Token::IF => {
+            let mut children = vec![first.into()];
+            parse_condition(input, &mut children)?;
+            children.push(parse_braced_block(input)?.into());
+            if peek(input) == Some(&Token::ELSE) {
+                // ELSE CONDITION
+                children.push(bump(input).into());
+                children.push(parse_braced_block(input)?.into());
             }
+            SyntaxNode::new(SyntaxKind::If, children)
         }
-        _ => panic!("The vector is empty"),
+
+        // while, control flow 1
+        Token::WHILE => {
+            // While (THING) { do thing}
+            let mut children = vec![first.into()];
+            parse_condition(input, &mut children)?;
+            children.push(parse_braced_block(input)?.into());
+            SyntaxNode::new(SyntaxKind::While, children)
+        }
+
+        // for in, iterates over a collection
+        Token::FOR => SyntaxNode::new(
+            // For (x in THING) { do thing}
+            SyntaxKind::For,
+            vec![
+                first.into(),
+                expect(input, Token::LPAR, "(")?.into(),
+                expect_ident(input, "a loop variable")?.into(),
+                expect(input, Token::IN, "in")?.into(),
+                parse_lowest(input)?.into(),
+                expect(input, Token::RPAR, ")")?.into(),
+                parse_braced_block(input)?.into(),
+            ],
+        ),
+
+        // Error
+        _ => return Err(unexpected("an expression", Some(first))),
     };
 
     // Depending on whether we have a prefix/infix expression, we need to modify evaluation order.
-    while precedence < input.last().unwrap().priority() {
-        left_expr = parse_infix(left_expr, input);
+    while precedence < peek(input).map_or(Precedence::Lowest, Token::priority) {
+        left = parse_infix(left, input)?;
     }
 
-    left_expr
+    Ok(left)
 }
 
 /// Parses expressions involving an operator in the middle, for instance a OP b.
 ///
 /// # Technical Information
-/// The left token is passed in, then the right token is popped, which results in a new infix expression, with pointers to the left and right expressions.
-fn parse_infix(left: Expr, input: &mut Vec<Token>) -> Expr {
-    let next_token = match input.pop() {
-        Some(value) => value,
-        None => panic!("Empty list..."),
-    };
-
-    let operator = match next_token {
-        Token::PLUS => Operator::Plus,
-        Token::MINUS => Operator::Minus,
-        Token::SLASH => Operator::Divide,
-        Token::MOD => Operator::Modulo,
-        Token::ASTERISK => Operator::Multiply,
-        Token::LEQ => Operator::LessThanEqual,
-        Token::LE => Operator::LessThan,
-        Token::GEQ => Operator::GreaterThanEqual,
-        Token::GR => Operator::GreaterThan,
-        Token::EQ => Operator::Equals,
-        Token::NEQ => Operator::NotEquals,
-        _ => panic!("parse infix called on invalid operator"),
-    };
+/// The left node is passed in, then the operator and right side are popped, which results in a new infix node holding the left and right nodes.
+fn parse_infix(left: SyntaxNode, input: &mut Vec<SyntaxToken>) -> Result<SyntaxNode, ParseError> {
+    let operator = bump(input);
+
+    // Indexing is treated as an infix operator whose right side is closed by a bracket.
+    if operator.token == Token::LBRACKET {
+        return Ok(SyntaxNode::new(
+            SyntaxKind::Index,
+            vec![
+                left.into(),
+                operator.into(),
+                parse_lowest(input)?.into(),
+                expect(input, Token::RBRACKET, "]")?.into(),
+            ],
+        ));
+    }
 
-    Expr::Infix {
-        left: Box::new(left),
-        operator,
-        right: Box::new(parse_expression(input, next_token.priority())),
+    if Operator::from_token(&operator.token).is_none() {
+        return Err(unexpected("an operator", Some(operator)));
     }
+    let right = parse_expression(input, operator.token.priority())?;
+    Ok(SyntaxNode::new(
+        SyntaxKind::Infix,
+        vec![left.into(), operator.into(), right.into()],
+    ))
 }
@@ -0,0 +1,111 @@
+//! Meta-commands are REPL lines starting with a colon, i.e `:help`. They inspect the session rather than being evaluated.
+use crate::eval::eval::Object;
+use crate::interpreter::error::Error;
+use crate::interpreter::interpreter::Interpreter;
+use crate::lexer::lexer::Lexer;
+use crate::parser::parser::parse;
+use std::time::Instant;
+
+const HELP: &str = "Meta-commands:
+  :help           shows this message
+  :env            lists the variables and functions defined so far
+  :load <file>    evaluates a file into the session
+  :reset          forgets everything defined so far
+  :tokens <code>  shows the tokens the lexer produces for some code
+  :ast <code>     shows the statements the parser produces for some code
+  :time <code>    evaluates some code and shows how long it took
+  :quit           closes the REPL";
+
+/// What the REPL should do once a meta-command has run.
+#[derive(Debug, PartialEq)]
+pub enum CommandResult {
+    /// Keep reading input after showing the message.
+    Continue(String),
+    Quit,
+    /// The code that was run called quwuit, so the REPL exits with its code as if it had been typed at the prompt.
+    Exit(i32),
+}
+
+/// Checks whether a line is a meta-command. :3 is a statement terminator, so a command must start with a letter.
+pub fn is_command(line: &str) -> bool {
+    let mut chars = line.trim_start().chars();
+    chars.next() == Some(':') && chars.next().is_some_and(char::is_alphabetic)
+}
+
+/// Runs a meta-command against the session, returning the message to show.
+pub fn run_command(line: &str, interpreter: &mut Interpreter) -> CommandResult {
+    let line = line.trim();
+    let (command, argument) = match line.find(char::is_whitespace) {
+        Some(split) => (&line[..split], line[split..].trim()),
+        None => (line, ""),
+    };
+
+    let message = match (command, argument) {
+        (":help", _) => String::from(HELP),
+        (":quit", _) => return CommandResult::Quit,
+        (":env", _) => show_env(interpreter),
+        (":reset", _) => {
+            interpreter.reset();
+            String::from("Everything has been forgotten.")
+        }
+        (":load", "") | (":tokens", "") | (":ast", "") | (":time", "") => {
+            format!("{} expects an argument, see :help", command)
+        }
+        (":load", file) => match interpreter.eval_file(file) {
+            Ok(_) => format!("Loaded {}", file),
+            Err(Error::Terminated(code)) => return CommandResult::Exit(code),
+            Err(error) => error.to_string(),
+        },
+        (":tokens", code) => {
+            let tokens: Vec<String> = Lexer::new(code)
+                .lex()
+                .iter()
+                .rev()
+                .map(|token| format!("{:?}", token))
+                .collect();
+            tokens.join("\n")
+        }
+        (":ast", code) => match parse(&mut Lexer::new(code).lex()) {
+            Ok(statements) => statements
+                .iter()
+                .map(|statement| format!("{:#?}", statement))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            Err(error) => format!("Parser error: {}", error),
+        },
+        (":time", code) => {
+            let start = Instant::now();
+            let result = interpreter.eval_str(code);
+            let elapsed = start.elapsed();
+            match result {
+                Ok(Object::Null) => format!("Took {:?}", elapsed),
+                Ok(value) => format!("{}\nTook {:?}", value, elapsed),
+                Err(Error::Terminated(code)) => return CommandResult::Exit(code),
+                Err(error) => format!("{}\nTook {:?}", error, elapsed),
+            }
+        }
+        _ => format!("Unknown command {}, see :help", command),
+    };
+    CommandResult::Continue(message)
+}
+
+/// Lists the global bindings by name, showing functions by their signature.
+fn show_env(interpreter: &Interpreter) -> String {
+    let mut bindings: Vec<(&String, &Object)> = interpreter.env().bindings().collect();
+    if bindings.is_empty() {
+        return String::from("Nothing has been defined yet.");
+    }
+    bindings.sort_by_key(|(name, _)| *name);
+    bindings
+        .iter()
+        .map(|(name, value)| match value {
+            Object::Function(function) => {
+                format!("uwu {}({})", name, function.parameters.join(", "))
+            }
+            // Quoted so strings can be told apart from other values.
+            Object::String(string) => format!("owo {} = {:?}", name, string),
+            value => format!("owo {} = {}", name, value),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,197 @@
+//! Hooks the REPL into rustyline's line editor, i.e to complete words and highlight code as it is typed.
+use crate::interpreter::interpreter::Interpreter;
+use crate::lexer::lexer::Lexer;
+use crate::token::token::{Token, KEYWORDS};
+use colored::*;
+use rustyline::completion::Completer;
+use rustyline::highlight::{CmdKind, Highlighter};
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper, Result};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::ops::Range;
+
+/// Holds what the editor needs to know about the session. The editor can't borrow the interpreter while it reads, so names are copied over after every input.
+#[derive(Default)]
+pub struct ReplHelper {
+    names: Vec<String>,
+    match_brackets: Cell<bool>, // Brackets are only matched while the line is being edited.
+}
+
+impl ReplHelper {
+    /// Instantiates a helper that completes keywords, and the builtins and bindings of the interpreter.
+    pub fn new(interpreter: &Interpreter) -> Self {
+        let mut helper = Self::default();
+        helper.refresh(interpreter);
+        helper
+    }
+
+    /// Refreshes the names that can be completed, since the input may have defined new ones.
+    pub fn refresh(&mut self, interpreter: &Interpreter) {
+        let mut names: Vec<String> = KEYWORDS.iter().map(|keyword| keyword.to_string()).collect();
+        names.extend(interpreter.natives().names().cloned());
+        // Bindings in enclosing scopes are not reachable from the top level, so only the global scope is searched.
+        names.extend(interpreter.env().bindings().map(|(name, _)| name.clone()));
+        names.sort();
+        names.dedup();
+        self.names = names;
+    }
+
+    /// Finds where the word under the cursor starts, and every name it could be completed to.
+    pub fn complete_word(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let start = line[..pos]
+            .char_indices()
+            .rev()
+            .take_while(|(_, chr)| chr.is_alphanumeric() || *chr == '_')
+            .last()
+            .map_or(pos, |(index, _)| index);
+        let word = &line[start..pos];
+        // Numbers are not names, and completing nothing would list everything.
+        if word.is_empty() || word.starts_with(|chr: char| chr.is_ascii_digit()) {
+            return (pos, vec![]);
+        }
+
+        let candidates = self
+            .names
+            .iter()
+            .filter(|name| name.starts_with(word))
+            .cloned()
+            .collect();
+        (start, candidates)
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Result<(usize, Vec<String>)> {
+        Ok(self.complete_word(line, pos))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, pos: usize) -> Cow<'l, str> {
+        let cursor = if self.match_brackets.get() {
+            Some(pos)
+        } else {
+            None
+        };
+        Cow::Owned(highlight(line, cursor))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, kind: CmdKind) -> bool {
+        // The final refresh happens once the line is submitted, where a highlighted bracket would be left behind.
+        self.match_brackets.set(kind != CmdKind::ForcedRefresh);
+        true
+    }
+}
+
+/// Colours a line using the tokens the lexer reads from it, comments included. If a cursor is given, the bracket at it and its match are highlighted too, or the bracket is shown in red if nothing closes it.
+pub fn highlight(line: &str, cursor: Option<usize>) -> String {
+    let tokens = Lexer::with_comments(line).lex_spans();
+    let brackets = cursor.and_then(|pos| matching_brackets(&tokens, pos));
+
+    let mut highlighted = String::new();
+    let mut last = 0;
+    for (index, (token, span)) in tokens.iter().enumerate() {
+        highlighted.push_str(&line[last..span.start]);
+        let text = &line[span.clone()];
+        let styled = match brackets {
+            Some((bracket, Some(pair))) if index == bracket || index == pair => {
+                text.bright_cyan().bold().underline()
+            }
+            Some((bracket, None)) if index == bracket => text.red().bold(),
+            _ => colour(token, text),
+        };
+        highlighted.push_str(&styled.to_string());
+        last = span.end;
+    }
+    highlighted.push_str(&line[last..]);
+    highlighted
+}
+
+/// Colours a token by its kind.
+fn colour(token: &Token, text: &str) -> ColoredString {
+    match token {
+        // Keywords lex from exactly their own text, so the text tells them apart from every other token.
+        _ if KEYWORDS.contains(&text) => text.magenta().bold(),
+        Token::STRING(_) => text.green(),
+        Token::INT(_) => text.yellow(),
+        Token::ASSIGN
+        | Token::PLUS
+        | Token::MINUS
+        | Token::ASTERISK
+        | Token::SLASH
+        | Token::MOD
+        | Token::EQ
+        | Token::LEQ
+        | Token::LE
+        | Token::GEQ
+        | Token::GR
+        | Token::NEQ
+        | Token::BANG
+        | Token::COLON => text.blue(),
+        Token::ILLEGAL(_) => text.white().on_red(),
+        Token::COMMENT(_) => text.bright_black(),
+        _ => text.normal(),
+    }
+}
+
+/// Finds the bracket under the cursor, or just before it, along with the bracket it pairs with if there is one. Returns the indices of the tokens.
+fn matching_brackets(
+    tokens: &[(Token, Range<usize>)],
+    pos: usize,
+) -> Option<(usize, Option<usize>)> {
+    let under = tokens
+        .iter()
+        .position(|(token, span)| span.start == pos && is_bracket(token));
+    let before = tokens
+        .iter()
+        .position(|(token, span)| span.end == pos && is_bracket(token));
+    let index = under.or(before)?;
+
+    let (open, close, forwards) = match tokens[index].0 {
+        Token::LPAR => (Token::LPAR, Token::RPAR, true),
+        Token::LBRA => (Token::LBRA, Token::RBRA, true),
+        Token::LBRACKET => (Token::LBRACKET, Token::RBRACKET, true),
+        Token::RPAR => (Token::LPAR, Token::RPAR, false),
+        Token::RBRA => (Token::LBRA, Token::RBRA, false),
+        _ => (Token::LBRACKET, Token::RBRACKET, false),
+    };
+
+    // Walk away from the bracket until every bracket of the same kind in between is closed.
+    let mut depth = 0;
+    let candidates: Box<dyn Iterator<Item = usize>> = if forwards {
+        Box::new(index..tokens.len())
+    } else {
+        Box::new((0..=index).rev())
+    };
+    for candidate in candidates {
+        let token = &tokens[candidate].0;
+        if *token == open {
+            depth += if forwards { 1 } else { -1 };
+        } else if *token == close {
+            depth += if forwards { -1 } else { 1 };
+        }
+        if depth == 0 {
+            return Some((index, Some(candidate)));
+        }
+    }
+    Some((index, None))
+}
+
+fn is_bracket(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::LPAR | Token::RPAR | Token::LBRA | Token::RBRA | Token::LBRACKET | Token::RBRACKET
+    )
+}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
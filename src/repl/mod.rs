@@ -1,4 +1,5 @@
-//! The REPL module contains the code that creates a REPL or evaluates a file.
+//! The REPL module contains the code that creates a REPL, along with the meta-commands it understands and its hooks into the line editor.
 
-pub mod interpreter;
+pub mod commands;
+pub mod helper;
 pub mod repl;
@@ -1,34 +1,194 @@
-use crate::eval::eval::{eval_return, Env, Object};
+use crate::cli::cli::EXIT_SUCCESS;
+use crate::eval::eval::{Object, GOODBYE};
+use crate::eval::native::NativeRegistry;
+use crate::interpreter::error::Error;
+use crate::interpreter::interpreter::Interpreter;
 use crate::lexer::lexer::Lexer;
 use crate::parser::parser::parse;
-use ::std::io::Write;
-use std::io;
-use std::process;
+use crate::repl::commands::{is_command, run_command, CommandResult};
+use crate::repl::helper::ReplHelper;
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::Editor;
+use std::env;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
 
 use colored::*;
 
 const PROMPT: &str = "( ᴜ ω ᴜ )⭜";
+const CONTINUATION_PROMPT: &str = "( ᴜ ω ᴜ )…";
+const HISTORY_FILE: &str = ".uwucode_history";
 
-pub fn start() {
+type ReplEditor = Editor<ReplHelper, DefaultHistory>;
+
+/// Runs the REPL until the input ends or quwuit is called, returning the exit code.
+///
+/// Input is read until it forms complete statements, showing a continuation prompt in the meantime. A blank line submits whatever has been typed so far, and Ctrl-C discards it.
+/// Errors are reported without ending the session, and anything a failed input defined is rolled back.
+/// Lines starting with a colon are meta-commands, see :help.
+/// Lines can be edited and recalled with the usual readline bindings, i.e the arrow keys and Ctrl-R, and history is kept in ~/.uwucode_history between sessions.
+/// Tab completes keywords, builtins and anything defined so far.
+pub fn start() -> i32 {
     println!("uwu *nuzzles* wewcome to uwucode! Is for me..? 🥺👉👈");
-    let mut env = Env::new();
+    let mut editor = ReplEditor::new().expect("Could not open the terminal");
+    let history = history_path();
+    if let Some(path) = &history {
+        // There is no history to load the first time the REPL is opened.
+        let _ = editor.load_history(path);
+    }
+
+    let code = run(&mut editor);
+
+    if let Some(path) = &history {
+        if let Err(error) = editor.save_history(path) {
+            eprintln!("Could not save history: {}", error);
+        }
+    }
+    code
+}
+
+/// Reads and evaluates input until the REPL should close.
+fn run(editor: &mut ReplEditor) -> i32 {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .context()
+        .set_natives(NativeRegistry::with_system(vec![]));
+    editor.set_helper(Some(ReplHelper::new(&interpreter)));
     loop {
-        print!("{}  ", PROMPT.truecolor(255, 69, 0));
-        std::io::stdout().flush().expect("Flushing failed");
-        let mut user_in: String = String::new();
-        io::stdin().read_line(&mut user_in).expect("Could not read");
-        let mut lexer = Lexer::new(&user_in as &str);
-        let mut token_vec = lexer.lex();
-
-        let parsed = parse(&mut token_vec);
-
-        let evaluated = match eval_return(parsed, &mut env) {
-            Object::Terminate => {
-                println!("{}", Object::Terminate);
-                process::exit(69);
+        if let Some(helper) = editor.helper_mut() {
+            helper.refresh(&interpreter);
+        }
+        let user_in = match read_input(editor) {
+            Ok(input) => input,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => return EXIT_SUCCESS,
+            Err(error) => {
+                eprintln!("Could not read input: {}", error);
+                return EXIT_SUCCESS;
             }
-            val => val,
         };
-        println!("{}", evaluated);
+        if user_in.trim().is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(user_in.trim_end());
+
+        if is_command(&user_in) {
+            match guard(&mut interpreter, |interpreter| {
+                run_command(&user_in, interpreter)
+            }) {
+                Some(CommandResult::Continue(message)) => println!("{}", message),
+                Some(CommandResult::Quit) => return EXIT_SUCCESS,
+                Some(CommandResult::Exit(code)) => return goodbye(&mut interpreter, code),
+                None => (),
+            }
+            continue;
+        }
+
+        let result = match guard(&mut interpreter, |interpreter| {
+            interpreter.eval_str(&user_in)
+        }) {
+            Some(result) => result,
+            None => continue,
+        };
+        let shown = match result {
+            Err(Error::Terminated(code)) => return goodbye(&mut interpreter, code),
+            // Statements such as dprint have already shown their output.
+            Ok(Object::Null) => Ok(()),
+            Ok(val) => interpreter
+                .context()
+                .output()
+                .write_line(&val.to_string())
+                .map_err(Error::from),
+            Err(error) => Err(error),
+        };
+        if let Err(error) = shown {
+            eprintln!("{}", error.to_string().red());
+        }
+    }
+}
+
+/// Says goodbye once quwuit has been called, returning the code the REPL exits with.
+fn goodbye(interpreter: &mut Interpreter, code: i32) -> i32 {
+    // The session ends either way, so a goodbye that can't be written is dropped.
+    let _ = interpreter.context().output().write_line(GOODBYE);
+    code
+}
+
+/// Runs some input, catching any panic so one bad input can't end the session. After a panic, the globals are rolled back to what they were before the input.
+fn guard<T, F>(interpreter: &mut Interpreter, action: F) -> Option<T>
+where
+    F: FnOnce(&mut Interpreter) -> T,
+{
+    let snapshot = interpreter.env().clone();
+    match panic::catch_unwind(AssertUnwindSafe(|| action(interpreter))) {
+        Ok(result) => Some(result),
+        Err(_) => {
+            interpreter.set_env(snapshot);
+            eprintln!(
+                "{}",
+                "Internal error, the session has been rolled back to before this input.".red()
+            );
+            None
+        }
+    }
+}
+
+/// Reads lines until they form complete statements.
+fn read_input(editor: &mut ReplEditor) -> Result<String, ReadlineError> {
+    let mut user_in = editor.readline(&prompt(PROMPT))? + "\n";
+    while !is_command(&user_in) && is_incomplete(&user_in) {
+        let line = editor.readline(&prompt(CONTINUATION_PROMPT))?;
+        if line.trim().is_empty() {
+            break;
+        }
+        user_in.push_str(&line);
+        user_in.push('\n');
+    }
+    Ok(user_in)
+}
+
+fn prompt(text: &str) -> String {
+    format!("{}  ", text.truecolor(255, 69, 0))
+}
+
+/// The history file lives in the home directory, if there is one.
+fn history_path() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(HISTORY_FILE))
+}
+
+/// Checks whether more input is needed to finish the statements typed so far, i.e an open brace, parenthesis, bracket, string or comment, or a missing :3.
+pub fn is_incomplete(source: &str) -> bool {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut in_comment = false;
+    let mut chars = source.chars().peekable();
+
+    // Unclosed strings and comments are found before lexing, since the lexer expects them to be closed.
+    while let Some(chr) = chars.next() {
+        match chr {
+            '"' if !in_comment => in_string = !in_string,
+            '/' if !in_string && !in_comment && chars.peek() == Some(&'*') => {
+                chars.next();
+                in_comment = true;
+            }
+            '*' if in_comment && chars.peek() == Some(&'/') => {
+                chars.next();
+                in_comment = false;
+            }
+            '(' | '{' | '[' if !in_string && !in_comment => depth += 1,
+            ')' | '}' | ']' if !in_string && !in_comment => depth -= 1,
+            _ => (),
+        }
+    }
+    if in_string || in_comment || depth > 0 {
+        return true;
+    }
+
+    match parse(&mut Lexer::new(source).lex()) {
+        Err(error) => error.is_incomplete(),
+        Ok(_) => false,
     }
 }
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use crate::analysis::arity::{check_arity, ArityMismatch};
+    use crate::analysis::diagnostic::line_col;
+    use crate::eval::native::{Arity, NativeRegistry};
+    use crate::parser::cst::parse_cst;
+
+    fn check(source: &str) -> Vec<ArityMismatch> {
+        check_arity(
+            &parse_cst(source).unwrap(),
+            &NativeRegistry::with_builtins(),
+        )
+    }
+
+    #[test]
+    fn test_examples_arity() {
+        for entry in std::fs::read_dir("examples").unwrap() {
+            let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            assert_eq!(check(&source), vec![]);
+        }
+    }
+
+    #[test]
+    fn test_user_function_mismatch() {
+        let source = "uwu add(a, b) { sugoi a + b; };\nowo x = add(1) + add(1, 2);";
+        assert_eq!(
+            check(source),
+            vec![ArityMismatch {
+                name: String::from("add"),
+                expected: Arity::Exact(2),
+                found: 1,
+                call: 40..46,
+                definition: Some(0..13),
+            }]
+        );
+        assert_eq!(&source[40..46], "add(1)");
+        assert_eq!(line_col(source, 40), (2, 9));
+    }
+
+    #[test]
+    fn test_native_mismatch() {
+        let mismatches = check("len(); range(1, 2, 3, 4); range(5);");
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(
+            mismatches[0].to_string(),
+            "len takes 1 argument but 0 were given"
+        );
+        assert_eq!(
+            mismatches[1].to_string(),
+            "range takes 1 to 3 arguments but 4 were given"
+        );
+        assert_eq!(mismatches[1].definition, None);
+    }
+
+    #[test]
+    fn test_rebound_names_skipped() {
+        // Once a name is bound more than once, which definition a call reaches isn't known.
+        assert_eq!(check("uwu f(a) {}; uwu f(a, b) {}; f(1);"), vec![]);
+        assert_eq!(check("uwu f(len) { sugoi len(); };"), vec![]);
+        assert_eq!(check("owo len = 1; len(1, 2);"), vec![]);
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use crate::cli::cli::{
+        run, EXIT_IO_ERROR, EXIT_PARSE_ERROR, EXIT_RUNTIME_ERROR, EXIT_SUCCESS, EXIT_UNFORMATTED,
+    };
+
+    fn run_args(args: &[&str]) -> i32 {
+        run(std::iter::once("uwucode").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(run_args(&["check", "examples/fib.uwu"]), EXIT_SUCCESS);
+        assert_eq!(run_args(&["check", "examples/missing.uwu"]), EXIT_IO_ERROR);
+        assert_eq!(run_args(&["eval", "-e", "owo = 1;"]), EXIT_PARSE_ERROR);
+        assert_eq!(run_args(&["eval", "-e", "1 / 0;"]), EXIT_RUNTIME_ERROR);
+    }
+
+    #[test]
+    fn test_quwuit_exit_code() {
+        assert_eq!(run_args(&["eval", "-e", "quwuit(3);"]), 3);
+        assert_eq!(run_args(&["eval", "-e", "quwuit();"]), 69);
+        assert_eq!(
+            run_args(&["eval", "-e", "quwuit(256);"]),
+            EXIT_RUNTIME_ERROR
+        );
+    }
+
+    #[test]
+    fn test_backend() {
+        let args = ["run", "--backend", "vm", "examples/factorial.uwu"];
+        assert_eq!(run_args(&args), EXIT_SUCCESS);
+        assert_eq!(
+            run_args(&["eval", "--backend", "vm", "-e", "1 / 0;"]),
+            EXIT_RUNTIME_ERROR
+        );
+        assert_eq!(
+            run_args(&["eval", "--backend", "vm", "-e", "quwuit(3);"]),
+            3
+        );
+    }
+
+    #[test]
+    fn test_compile() {
+        let path =
+            std::env::temp_dir().join(format!("uwucode_compile_{}.uwuc", std::process::id()));
+        let file = path.to_str().unwrap();
+        let args = ["compile", "examples/fib.uwu", "-o", file];
+        assert_eq!(run_args(&args), EXIT_SUCCESS);
+        assert_eq!(run_args(&["run", file]), EXIT_SUCCESS);
+        assert_eq!(run_args(&["disasm", file]), EXIT_SUCCESS);
+        std::fs::write(&path, b"UWUC").unwrap();
+        assert_eq!(run_args(&["run", file]), EXIT_PARSE_ERROR);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_fmt() {
+        let path = std::env::temp_dir().join(format!("uwucode_fmt_{}.uwu", std::process::id()));
+        let file = path.to_str().unwrap();
+        std::fs::write(&path, "owo x=1:3").unwrap();
+        assert_eq!(run_args(&["fmt", "--check", file]), EXIT_UNFORMATTED);
+        assert_eq!(run_args(&["fmt", "--terminator", ":3", file]), EXIT_SUCCESS);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "owo x = 1:3\n");
+        assert_eq!(
+            run_args(&["fmt", "--check", "--terminator", ":3", file]),
+            EXIT_SUCCESS
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_check_names() {
+        let path = std::env::temp_dir().join(format!("uwucode_check_{}.uwu", std::process::id()));
+        let file = path.to_str().unwrap();
+        std::fs::write(&path, "owo len = 1; dprint(len);").unwrap();
+        // Shadowing a builtin is only a warning.
+        assert_eq!(run_args(&["check", file]), EXIT_SUCCESS);
+        std::fs::write(&path, "dprint(missing);").unwrap();
+        assert_eq!(run_args(&["check", file]), EXIT_PARSE_ERROR);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
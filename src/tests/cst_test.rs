@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use crate::lexer::lexer::Lexer;
+    use crate::lexer::trivia::Trivia;
+    use crate::parser::cst::{parse_cst, SyntaxKind};
+    use crate::parser::parser::parse;
+    use crate::token::token::Token;
+
+    const SOURCES: &[&str] = &[
+        "",
+        "  /* only a comment */\n",
+        "owo x = 1 :3 /* one */",
+        "uwu f(a, b) { sugoi a + b * (a - 1); };\nf(1, 2)[0];",
+        "owo m = {\"a\": 3, /* mid */ \"b\": [1, -2, !truwu]};\nm[\"a\"] = 5;",
+        "nuzzles (x) { dprint(1); } rawr {};\nnyaa (fowose) { bweak; };\nfur (i in [1]) { continyue; };",
+        "\t dprint(\"unclosed\"); /* runs to the end",
+    ];
+
+    #[test]
+    fn test_lossless_tokens() {
+        for source in SOURCES {
+            let tokens = Lexer::new(source).lex_lossless();
+            let text: String = tokens.iter().map(ToString::to_string).collect();
+            assert_eq!(&text, source);
+            assert_eq!(tokens.last().map(|token| &token.token), Some(&Token::EOF));
+        }
+
+        let tokens = Lexer::new("owo /* a */ x").lex_lossless();
+        assert_eq!(tokens[1].token, Token::IDENT(String::from("x")));
+        assert_eq!(
+            tokens[1].leading,
+            vec![
+                Trivia::Whitespace(String::from(" ")),
+                Trivia::Comment(String::from("/* a */")),
+                Trivia::Whitespace(String::from(" ")),
+            ]
+        );
+        assert_eq!(tokens[1].span, 12..13);
+    }
+
+    #[test]
+    fn test_cst_round_trip() {
+        let mut sources: Vec<String> = SOURCES.iter().map(|source| source.to_string()).collect();
+        for entry in std::fs::read_dir("examples").unwrap() {
+            sources.push(std::fs::read_to_string(entry.unwrap().path()).unwrap());
+        }
+        for source in sources.iter() {
+            let tree = parse_cst(source).unwrap();
+            assert_eq!(&tree.to_string(), source);
+            assert_eq!(tree.to_program(), parse(&mut Lexer::new(source).lex()).ok());
+        }
+    }
+
+    #[test]
+    fn test_cst_spans() {
+        let source = "owo x = 1;\n/* add */ x + 2;";
+        let tree = parse_cst(source).unwrap();
+        let statements: Vec<_> = tree.nodes().collect();
+        assert_eq!(statements[0].kind(), SyntaxKind::Let);
+        assert_eq!(statements[1].kind(), SyntaxKind::ExpressionStatement);
+        assert_eq!(&source[statements[1].span()], "x + 2;");
+        let infix = statements[1].nodes().next().unwrap();
+        assert_eq!(infix.kind(), SyntaxKind::Infix);
+        // Trivia before the first token belongs to the node.
+        assert_eq!(infix.to_string(), "\n/* add */ x + 2");
+    }
+
+    #[test]
+    fn test_cst_errors() {
+        for source in ["owo = 1;", "nyaa (truwu) {", "bweak;", "}"].iter() {
+            assert_eq!(
+                parse_cst(source).err(),
+                parse(&mut Lexer::new(source).lex()).err()
+            );
+        }
+    }
+}
@@ -0,0 +1,316 @@
+#[cfg(test)]
+mod tests {
+    use crate::eval::context::Context;
+    use crate::eval::eval::{eval_return, Env};
+    use crate::eval::input::ScriptedInput;
+    use crate::eval::native::NativeRegistry;
+    use crate::eval::output::BufferOutput;
+    use crate::interpreter::interpreter::Backend;
+    use crate::lexer::lexer::Lexer;
+    use crate::parser::ast::{Expr, Operator, Prefix, Statement};
+    use crate::parser::parser::parse;
+    use crate::vm::compiler::compile;
+    use crate::vm::uwuc::{decode, encode};
+    use crate::vm::vm;
+    use proptest::prelude::*;
+    use std::fs;
+    use std::rc::Rc;
+
+    /// Every script in these directories is run on both backends.
+    const CORPUS: &[&str] = &["examples", "src/tests/corpus"];
+    /// The lines nya_input reads, the same for every run.
+    const INPUT: &[&str] = &["uwu", "owo", "nyaa", "done", "rawr"];
+    /// Deep recursion would overflow the test thread's stack on the tree-walker.
+    const MAX_CALL_DEPTH: usize = 50;
+
+    /// Everything a program does that can be seen from outside. Functions are different objects on each backend, so values are compared by how they print.
+    #[derive(Debug, PartialEq)]
+    struct Outcome {
+        output: String,
+        /// The value of the program, or the error or termination that stopped it.
+        result: Result<String, String>,
+        /// The globals left once the program stops, sorted by name.
+        globals: Vec<(String, String)>,
+    }
+
+    fn run(statements: Vec<Statement>, backend: Backend) -> Outcome {
+        let output = BufferOutput::new();
+        let input = ScriptedInput::new(INPUT.to_vec());
+        let mut ctx = Context::with_io(Box::new(output.clone()), Box::new(input));
+        ctx.set_natives(NativeRegistry::with_system(vec![String::from("arg")]));
+        ctx.set_max_call_depth(MAX_CALL_DEPTH);
+        let mut env = Env::new();
+        let result = match backend {
+            Backend::TreeWalker => eval_return(statements, &mut env, &mut ctx),
+            // Going through a .uwuc file checks that everything the compiler emits passes the checks made when loading.
+            Backend::Vm => {
+                let program = decode(&encode(&compile(&statements))).expect("compiled code loads");
+                vm::run(Rc::new(program), &mut env, &mut ctx)
+            }
+        };
+        let mut globals: Vec<(String, String)> = env
+            .bindings()
+            .map(|(name, value)| (name.clone(), value.to_string()))
+            .collect();
+        globals.sort();
+        Outcome {
+            output: output.contents(),
+            result: result
+                .map(|value| value.to_string())
+                .map_err(|signal| format!("{:?}", signal)),
+            globals,
+        }
+    }
+
+    #[test]
+    fn test_corpus() {
+        let mut scripts = 0;
+        for directory in CORPUS {
+            for entry in fs::read_dir(directory).unwrap() {
+                let path = entry.unwrap().path();
+                if path.extension().and_then(|extension| extension.to_str()) != Some("uwu") {
+                    continue;
+                }
+                let source = fs::read_to_string(&path).unwrap();
+                let statements = parse(&mut Lexer::new(&source).lex())
+                    .unwrap_or_else(|error| panic!("{} does not parse: {}", path.display(), error));
+                assert_eq!(
+                    run(statements.clone(), Backend::TreeWalker),
+                    run(statements, Backend::Vm),
+                    "backends disagree on {}",
+                    path.display()
+                );
+                scripts += 1;
+            }
+        }
+        assert!(scripts > 10);
+    }
+
+    #[test]
+    fn test_corpus_errors() {
+        let error = |name: &str| {
+            let source = fs::read_to_string(format!("src/tests/corpus/{}.uwu", name)).unwrap();
+            run(parse(&mut Lexer::new(&source).lex()).unwrap(), Backend::Vm).result
+        };
+        // The corpus is only useful if its scripts fail the way their names say.
+        assert!(error("division_by_zero")
+            .unwrap_err()
+            .contains("DivisionByZero"));
+        assert!(error("call_depth")
+            .unwrap_err()
+            .contains("CallDepthExceeded"));
+        assert!(error("wrong_arity").unwrap_err().contains("WrongArity"));
+        assert_eq!(error("quwuit"), Err(String::from("Terminate(3)")));
+    }
+
+    /// The names generated code reads, calls and defines. Some are builtins and the rest may or may not be defined by the time they are used.
+    const NAMES: &[&str] = &["a", "b", "f", "len", "dprint", "keys", "quwuit"];
+    const VARIABLES: &[&str] = &["a", "b", "f"];
+    const PREFIXES: &[Prefix] = &[Prefix::Bang, Prefix::Minus];
+    const OPERATORS: &[Operator] = &[
+        Operator::Plus,
+        Operator::Minus,
+        Operator::Multiply,
+        Operator::Divide,
+        Operator::Modulo,
+        Operator::GreaterThan,
+        Operator::GreaterThanEqual,
+        Operator::LessThan,
+        Operator::LessThanEqual,
+        Operator::Equals,
+        Operator::NotEquals,
+    ];
+    /// How many levels of expressions and blocks are nested, which keeps generated programs quick to run.
+    const DEPTH: usize = 3;
+
+    /// The innermost loop generated code is inside of, which decides whether it may use bweak and continyue. Function bodies are inside of no loop.
+    #[derive(Clone, Copy, PartialEq)]
+    enum Within {
+        Nothing = 0,
+        For = 1,
+        While = 2,
+    }
+    const WITHIN: [Within; 3] = [Within::Nothing, Within::For, Within::While];
+
+    /// Strategies for one level of nesting, with one expression and one block strategy for each value of Within.
+    struct Level {
+        exprs: Vec<BoxedStrategy<Expr>>,
+        blocks: Vec<BoxedStrategy<Vec<Statement>>>,
+    }
+
+    fn name(names: &'static [&'static str]) -> BoxedStrategy<String> {
+        prop::sample::select(names).prop_map(String::from).boxed()
+    }
+
+    fn leaf() -> BoxedStrategy<Expr> {
+        // Mostly integers, which most operators accept, so fewer programs stop at their first operation.
+        prop_oneof![
+            4 => prop_oneof![0..100i64, Just(i64::MAX)].prop_map(Expr::Integer),
+            // The parser reads a negative literal as minus applied to a positive one.
+            1 => prop_oneof![1..100i64, Just(i64::MAX)].prop_map(|num| Expr::Prefix {
+                prefix: Prefix::Minus,
+                value: Box::new(Expr::Integer(num)),
+            }),
+            1 => "[a-z ]{0,4}".prop_map(Expr::String),
+            1 => any::<bool>().prop_map(Expr::Boolean),
+            2 => name(NAMES).prop_map(Expr::Variable),
+        ]
+        .boxed()
+    }
+
+    fn parameters() -> BoxedStrategy<Vec<String>> {
+        prop::sample::subsequence(VARIABLES, 0..3)
+            .prop_map(|names| names.into_iter().map(String::from).collect())
+            .boxed()
+    }
+
+    /// Statements whose expressions come from the given strategy. Bodies of functions come from the level below, so generating them always ends.
+    fn statement(
+        value: BoxedStrategy<Expr>,
+        body: BoxedStrategy<Vec<Statement>>,
+        within: Within,
+    ) -> BoxedStrategy<Statement> {
+        let mut options = vec![
+            value.clone().prop_map(Statement::Expression).boxed(),
+            (name(VARIABLES), value.clone())
+                .prop_map(|(name, value)| Statement::Let { name, value })
+                .boxed(),
+            value
+                .clone()
+                .prop_map(|value| Statement::Return { value })
+                .boxed(),
+            (name(VARIABLES), value.clone(), value)
+                .prop_map(|(name, index, value)| Statement::IndexAssign { name, index, value })
+                .boxed(),
+            (name(VARIABLES), parameters(), body)
+                .prop_map(|(func_name, parameters, body)| Statement::Define {
+                    func_name,
+                    func: Expr::Function { parameters, body },
+                })
+                .boxed(),
+        ];
+        if within != Within::Nothing {
+            options.push(Just(Statement::Break).boxed());
+        }
+        // A nyaa loop only ends through bweak, so continyue could skip it forever.
+        if within == Within::For {
+            options.push(Just(Statement::Continue).boxed());
+        }
+        prop::strategy::Union::new(options).boxed()
+    }
+
+    fn block(statement: BoxedStrategy<Statement>) -> BoxedStrategy<Vec<Statement>> {
+        prop::collection::vec(statement, 0..3).boxed()
+    }
+
+    /// The first level only holds literals and names, and blocks made of them.
+    fn first_level() -> Level {
+        let exprs: Vec<_> = WITHIN.iter().map(|_| leaf()).collect();
+        let blocks = WITHIN
+            .iter()
+            .map(|&within| {
+                let simple = prop_oneof![
+                    leaf().prop_map(Statement::Expression),
+                    (name(VARIABLES), leaf())
+                        .prop_map(|(name, value)| Statement::Let { name, value }),
+                ]
+                .boxed();
+                let mut options = vec![simple];
+                if within != Within::Nothing {
+                    options.push(Just(Statement::Break).boxed());
+                }
+                block(prop::strategy::Union::new(options).boxed())
+            })
+            .collect();
+        Level { exprs, blocks }
+    }
+
+    /// Builds a level whose expressions are made of those from the level below.
+    fn next_level(below: &Level) -> Level {
+        let exprs: Vec<_> = WITHIN.iter().map(|&within| expr(below, within)).collect();
+        let blocks = WITHIN
+            .iter()
+            .map(|&within| {
+                let body = below.blocks[Within::Nothing as usize].clone();
+                block(statement(exprs[within as usize].clone(), body, within))
+            })
+            .collect();
+        Level { exprs, blocks }
+    }
+
+    fn expr(below: &Level, within: Within) -> BoxedStrategy<Expr> {
+        let operand = below.exprs[within as usize].clone();
+        let blocks = |within: Within| below.blocks[within as usize].clone();
+        prop_oneof![
+            2 => leaf(),
+            1 => prop::collection::vec(operand.clone(), 0..3).prop_map(Expr::Array),
+            1 => prop::collection::vec((operand.clone(), operand.clone()), 0..3).prop_map(Expr::Map),
+            1 => (operand.clone(), operand.clone()).prop_map(|(left, index)| Expr::Index {
+                left: Box::new(left),
+                index: Box::new(index),
+            }),
+            1 => (prop::sample::select(PREFIXES), operand.clone()).prop_map(|(prefix, value)| {
+                Expr::Prefix {
+                    prefix,
+                    value: Box::new(value),
+                }
+            }),
+            2 => (operand.clone(), prop::sample::select(OPERATORS), operand.clone()).prop_map(
+                |(left, operator, right)| Expr::Infix {
+                    left: Box::new(left),
+                    operator,
+                    right: Box::new(right),
+                }
+            ),
+            1 => (operand.clone(), blocks(within), blocks(within)).prop_map(
+                |(condition, consequence, alternative)| Expr::If {
+                    condition: Box::new(condition),
+                    consequence,
+                    alternative,
+                }
+            ),
+            // Ending the body with bweak makes sure the loop does.
+            1 => (operand.clone(), blocks(Within::While)).prop_map(|(condition, mut instruction)| {
+                instruction.push(Statement::Break);
+                Expr::While {
+                    condition: Box::new(condition),
+                    instruction,
+                }
+            }),
+            1 => (name(VARIABLES), operand.clone(), blocks(Within::For)).prop_map(
+                |(variable, iterable, body)| Expr::For {
+                    variable,
+                    iterable: Box::new(iterable),
+                    body,
+                }
+            ),
+            1 => (name(NAMES), prop::collection::vec(operand, 0..3)).prop_map(
+                |(function, arguments)| Expr::Call {
+                    function: Box::new(Expr::Variable(function)),
+                    arguments,
+                }
+            ),
+        ]
+        .boxed()
+    }
+
+    /// Random well-formed expressions, as the parser could produce, outside of any loop.
+    fn expressions() -> BoxedStrategy<Expr> {
+        let mut level = first_level();
+        for _ in 0..DEPTH {
+            level = next_level(&level);
+        }
+        level.exprs[Within::Nothing as usize].clone()
+    }
+
+    proptest! {
+        #[test]
+        fn test_random_expressions(exprs in prop::collection::vec(expressions(), 1..4)) {
+            let statements: Vec<Statement> = exprs.into_iter().map(Statement::Expression).collect();
+            prop_assert_eq!(
+                run(statements.clone(), Backend::TreeWalker),
+                run(statements, Backend::Vm)
+            );
+        }
+    }
+}
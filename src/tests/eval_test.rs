@@ -0,0 +1,298 @@
+#[cfg(test)]
+mod tests {
+    use crate::eval::builtins::MAX_RANGE_LENGTH;
+    use crate::eval::eval::{eval_return, Context, ControlFlow, Env, MapKey, Object, RuntimeError};
+    use crate::eval::native::Arity;
+    use crate::lexer::lexer::Lexer;
+    use crate::parser::parser::parse;
+    use std::collections::BTreeMap;
+
+    fn eval_with(input: &str, ctx: &mut Context) -> Result<Object, ControlFlow> {
+        let mut token_vec = Lexer::new(input).lex();
+        let parsed = parse(&mut token_vec).expect("Parsing failed");
+        eval_return(parsed, &mut Env::new(), ctx)
+    }
+
+    fn eval_result(input: &str) -> Result<Object, ControlFlow> {
+        eval_with(input, &mut Context::new())
+    }
+
+    fn eval_input(input: &str) -> Object {
+        eval_result(input).expect("Evaluation was interrupted")
+    }
+
+    #[test]
+    fn test_map_literal() {
+        let mut expected = BTreeMap::new();
+        expected.insert(MapKey::String(String::from("a")), Object::Integer(1));
+        expected.insert(MapKey::Integer(2), Object::Boolean(true));
+        expected.insert(MapKey::Boolean(false), Object::String(String::from("b")));
+
+        assert_eq!(
+            eval_input("{\"a\": 1, 2: truwu, fowose: \"b\"};"),
+            Object::Map(expected)
+        );
+        assert_eq!(eval_input("{};"), Object::Map(BTreeMap::new()));
+
+        // Keys may be any expression, not only a single token.
+        let mut expected = BTreeMap::new();
+        expected.insert(MapKey::Integer(-1), Object::Integer(2));
+        expected.insert(MapKey::Integer(1), Object::Integer(3));
+        expected.insert(MapKey::Integer(4), Object::Integer(5));
+        assert_eq!(
+            eval_input("owo k = 3; {-1: 2, len(\"a\"): 3, k + 1: 5};"),
+            Object::Map(expected)
+        );
+    }
+
+    #[test]
+    fn test_map_index_and_insert() {
+        let input = "owo m = {\"uwu\": 1} :3
+        m[\"owo\"] = 2 :3
+        m[\"uwu\"] = m[\"uwu\"] + m[\"owo\"] :3
+        m[\"uwu\"] :3";
+        assert_eq!(eval_input(input), Object::Integer(3));
+    }
+
+    #[test]
+    fn test_map_builtins() {
+        let input = "owo m = {\"b\": 2, \"a\": 1};";
+        assert_eq!(
+            eval_input(&format!("{} keys(m);", input)).to_string(),
+            "[\"a\", \"b\"]"
+        );
+        assert_eq!(
+            eval_input(&format!("{} values(m);", input)).to_string(),
+            "[1, 2]"
+        );
+        assert_eq!(
+            eval_input(&format!("{} has(m, \"a\");", input)),
+            Object::Boolean(true)
+        );
+        assert_eq!(
+            eval_input(&format!(
+                "{} owo m = remove(m, \"a\"); has(m, \"a\");",
+                input
+            )),
+            Object::Boolean(false)
+        );
+        assert_eq!(
+            eval_input(&format!("{} len(m);", input)),
+            Object::Integer(2)
+        );
+    }
+
+    #[test]
+    fn test_array_index() {
+        assert_eq!(eval_input("[1, 2, 3][1];"), Object::Integer(2));
+        assert_eq!(
+            eval_input("owo a = [1, 2]; a[0] = 5; a;"),
+            Object::Array(vec![Object::Integer(5), Object::Integer(2)])
+        );
+    }
+
+    #[test]
+    fn test_while_loop() {
+        let input = "owo i = 0; owo total = 0;
+        nyaa (i < 5) { owo total = total + i; owo i = i + 1; };
+        total;";
+        assert_eq!(eval_input(input), Object::Integer(10));
+    }
+
+    #[test]
+    fn test_for_loop() {
+        assert_eq!(
+            eval_input("owo total = 0; fur (x in [1, 2, 3]) { owo total = total + x; }; total;"),
+            Object::Integer(6)
+        );
+        assert_eq!(
+            eval_input("owo n = 0; fur (c in \"uwu\") { owo n = n + len(c); }; n;"),
+            Object::Integer(3)
+        );
+        assert_eq!(
+            eval_input("owo n = 0; fur (k in {\"a\": 1, \"b\": 2}) { owo n = n + 1; }; n;"),
+            Object::Integer(2)
+        );
+        assert_eq!(
+            eval_input(
+                "owo total = 0; fur (x in range(10, 0, -3)) { owo total = total + x; }; total;"
+            ),
+            Object::Integer(22)
+        );
+    }
+
+    #[test]
+    fn test_range_stops_before_overflow() {
+        assert_eq!(
+            eval_input("len(range(9223372036854775806, 9223372036854775807, 5));"),
+            Object::Integer(1)
+        );
+        assert_eq!(
+            eval_input("len(range(-9223372036854775807, -9223372036854775807 - 1, -3));"),
+            Object::Integer(1)
+        );
+
+        // Huge ranges are refused before anything is allocated, even if the loop would stop early.
+        for input in [
+            "range(9223372036854775807);",
+            "range(-9223372036854775807 - 1, 9223372036854775807);",
+            "fur (i in range(0, 100000000000)) { bweak; };",
+        ] {
+            assert!(matches!(
+                eval_result(input),
+                Err(ControlFlow::Error(RuntimeError::Custom(_)))
+            ));
+        }
+        assert_eq!(
+            eval_input(&format!("len(range({}));", MAX_RANGE_LENGTH)),
+            Object::Integer(MAX_RANGE_LENGTH as i64)
+        );
+    }
+
+    #[test]
+    fn test_for_loop_scope() {
+        // The loop variable does not outlive the loop, and returns unwind out of the body.
+        let input = "owo x = 7;
+        uwu first_even(xs) { fur (x in xs) { nuzzles (x % 2 == 0) { sugoi x; }; }; sugoi -1; };
+        first_even([1, 3, 4, 6]) + x;";
+        assert_eq!(eval_input(input), Object::Integer(11));
+    }
+
+    #[test]
+    fn test_break_and_continue() {
+        let input = "owo i = 0; owo total = 0;
+        nyaa (truwu) {
+            owo i = i + 1;
+            nuzzles (i > 10) { bweak; };
+            nuzzles (i % 2 == 0) { nuzzles (truwu) { continyue; }; };
+            owo total = total + i;
+        };
+        total;";
+        assert_eq!(eval_input(input), Object::Integer(25));
+
+        let input = "owo total = 0;
+        fur (x in range(10)) {
+            nuzzles (x == 3) { continyue; };
+            nuzzles (x == 6) { bweak; };
+            owo total = total + x;
+        };
+        total;";
+        assert_eq!(eval_input(input), Object::Integer(12));
+    }
+
+    #[test]
+    fn test_control_flow_signals() {
+        // Returns unwind through loops and conditionals to the enclosing function only.
+        let input = "uwu f() { nyaa (truwu) { nuzzles (truwu) { sugoi 1; }; }; };
+        f() + 1;";
+        assert_eq!(eval_input(input), Object::Integer(2));
+
+        // Termination unwinds through every frame, no matter how deeply it was called.
+        let input =
+            "uwu f(x) { fur (y in range(x)) { nuzzles (y == 2) { quwuit(); }; }; sugoi 0; };
+        f(5);
+        1;";
+        assert_eq!(eval_result(input), Err(ControlFlow::Terminate(69)));
+        assert_eq!(eval_result("quwuit(3);"), Err(ControlFlow::Terminate(3)));
+        assert_eq!(
+            eval_result("quwuit(255);"),
+            Err(ControlFlow::Terminate(255))
+        );
+        for code in ["256", "-1"] {
+            assert!(matches!(
+                eval_result(&format!("quwuit({});", code)),
+                Err(ControlFlow::Error(RuntimeError::Custom(_)))
+            ));
+        }
+
+        // dprint is a side effect, and evaluates to nothing.
+        assert_eq!(eval_input("uwu f() { dprint(1); }; f();"), Object::Null);
+    }
+
+    #[test]
+    fn test_native_functions() {
+        let mut ctx = Context::new();
+        ctx.natives_mut().register("double", 1, |args| match args {
+            [Object::Integer(num)] => Ok(Object::Integer(num * 2)),
+            _ => Err(RuntimeError::Custom(String::from(
+                "double expects an integer",
+            ))),
+        });
+
+        assert_eq!(eval_with("double(21);", &mut ctx), Ok(Object::Integer(42)));
+        assert_eq!(
+            eval_with("double(\"uwu\");", &mut ctx),
+            Err(ControlFlow::Error(RuntimeError::Custom(String::from(
+                "double expects an integer"
+            ))))
+        );
+        assert_eq!(
+            eval_with("double(1, 2);", &mut ctx),
+            Err(ControlFlow::Error(RuntimeError::WrongArity {
+                name: String::from("double"),
+                expected: Arity::Exact(1),
+                found: 2,
+            }))
+        );
+
+        // Natives are ordinary values, and bindings shadow them.
+        assert_eq!(
+            eval_with("owo measure = len; measure([1, 2]);", &mut ctx),
+            Ok(Object::Integer(2))
+        );
+        assert_eq!(
+            eval_with("uwu len(x) { sugoi 0; }; len(\"uwu\");", &mut ctx),
+            Ok(Object::Integer(0))
+        );
+    }
+
+    #[test]
+    fn test_runtime_errors() {
+        assert_eq!(
+            eval_result("nyaa_nyaa + 1;"),
+            Err(ControlFlow::Error(RuntimeError::UndefinedVariable(
+                String::from("nyaa_nyaa")
+            )))
+        );
+        assert_eq!(
+            eval_result("1 % 0;"),
+            Err(ControlFlow::Error(RuntimeError::DivisionByZero))
+        );
+        assert_eq!(
+            eval_result("[1][3];"),
+            Err(ControlFlow::Error(RuntimeError::IndexOutOfRange {
+                index: 3,
+                length: 1
+            }))
+        );
+        assert_eq!(
+            eval_result("uwu f(x) { sugoi x; }; f();"),
+            Err(ControlFlow::Error(RuntimeError::WrongArity {
+                name: String::from("f"),
+                expected: Arity::Exact(1),
+                found: 0,
+            }))
+        );
+        assert_eq!(eval_input("1 != 2;"), Object::Boolean(true));
+        assert_eq!(
+            eval_result("9223372036854775807 + 1;"),
+            Err(ControlFlow::Error(RuntimeError::IntegerOverflow))
+        );
+    }
+
+    #[test]
+    fn test_call_depth() {
+        let mut ctx = Context::new();
+        ctx.set_max_call_depth(50);
+        let countdown = "uwu f(x) { nuzzles (x == 0) { sugoi 0; }; sugoi f(x - 1); };";
+
+        assert_eq!(
+            eval_with(&format!("{} f(49);", countdown), &mut ctx),
+            Ok(Object::Integer(0))
+        );
+        assert_eq!(
+            eval_with(&format!("{} f(50);", countdown), &mut ctx),
+            Err(ControlFlow::Error(RuntimeError::CallDepthExceeded(50)))
+        );
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use crate::formatter::formatter::{
+        first_changed_line, format_source, is_formatted, FormatOptions, Terminator,
+    };
+    use crate::lexer::lexer::Lexer;
+    use crate::parser::parser::parse;
+
+    fn format(source: &str) -> String {
+        format_source(source, &FormatOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_spacing() {
+        assert_eq!(
+            format("owo x=-a+b[1]*f(2,-3)- -1:3"),
+            "owo x = -a + b[1] * f(2, -3) - -1;\n"
+        );
+        assert_eq!(
+            format("owo m={\"a\": 3,\"b\":[!truwu]};"),
+            "owo m = {\"a\": 3, \"b\": [!truwu]};\n"
+        );
+    }
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(
+            format("uwu f(x){nuzzles(x){sugoi 1;}rawr{};nyaa (x) {bweak;};}:3"),
+            "uwu f(x) {\n    nuzzles (x) {\n        sugoi 1;\n    }\n    rawr {};\n    nyaa (x) {\n        bweak;\n    };\n};\n"
+        );
+    }
+
+    #[test]
+    fn test_layout_from_tree() {
+        // Braces, brackets and minus signs are laid out by what they belong to rather than what comes before them.
+        assert_eq!(
+            format("owo m={ \"k\":-(1) }; f (1) [0] ;uwu h(){ /* empty */ };"),
+            "owo m = {\"k\": -(1)};\nf(1)[0];\nuwu h() { /* empty */\n};\n"
+        );
+    }
+
+    #[test]
+    fn test_comments_kept() {
+        let source = "/* top */\nowo x = 1; /* trailing */\n\n\n\nuwu f() {\n    /* inside */\n    sugoi x;\n}; /* unclosed";
+        assert_eq!(
+            format(source),
+            "/* top */\nowo x = 1; /* trailing */\n\nuwu f() {\n    /* inside */\n    sugoi x;\n}; /* unclosed*/\n"
+        );
+    }
+
+    #[test]
+    fn test_terminator() {
+        let options = FormatOptions {
+            terminator: Terminator::Uwu,
+        };
+        assert_eq!(
+            format_source("owo x = 1; x;", &options).unwrap(),
+            "owo x = 1:3\nx:3\n"
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let options = FormatOptions::default();
+        assert_eq!(is_formatted("owo x = 1;\n", &options), Ok(true));
+        assert_eq!(is_formatted("owo x=1;\n", &options), Ok(false));
+        assert!(is_formatted("owo = 1;", &options).is_err());
+        assert_eq!(first_changed_line("a\nb\n", "a\nc\n"), Some(2));
+        assert_eq!(first_changed_line("a\nb", "a\nb\n"), Some(2));
+        assert_eq!(first_changed_line("a\n", "a\n"), None);
+    }
+
+    #[test]
+    fn test_examples_stable() {
+        for entry in std::fs::read_dir("examples").unwrap() {
+            let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            let formatted = format(&source);
+            assert_eq!(format(&formatted), formatted);
+            assert_eq!(
+                parse(&mut Lexer::new(&formatted).lex()),
+                parse(&mut Lexer::new(&source).lex())
+            );
+        }
+    }
+}
@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests {
+    use crate::eval::context::Context;
+    use crate::eval::eval::{Object, RuntimeError};
+    use crate::eval::input::ScriptedInput;
+    use crate::eval::native::NativeRegistry;
+    use crate::eval::output::{BufferOutput, FileOutput};
+    use crate::interpreter::error::Error;
+    use crate::interpreter::interpreter::Interpreter;
+    use crate::parser::error::ParseError;
+    use std::fs;
+
+    fn run_file(file_name: &str) -> (Result<Object, Error>, String) {
+        let output = BufferOutput::new();
+        let mut interpreter =
+            Interpreter::with_context(Context::with_output(Box::new(output.clone())));
+        let result = interpreter.eval_file(file_name);
+        (result, output.contents())
+    }
+
+    #[test]
+    fn test_prints_example() {
+        let (result, output) = run_file("examples/prints.uwu");
+        assert!(result.is_ok());
+        assert_eq!(output, "Hello world!\n7\n");
+    }
+
+    #[test]
+    fn test_fizzbuzz_example() {
+        let (result, output) = run_file("examples/rec_fizzbuzz.uwu");
+        assert!(result.is_ok());
+        // Printing works inside of the recursive calls, which stop at the first number without a branch.
+        assert_eq!(output, "Buzz\nFizz\n");
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let (result, _) = run_file("examples/does_not_exist.uwu");
+        assert!(matches!(result, Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn test_file_output() {
+        let path = std::env::temp_dir().join("uwucode_file_output_test.txt");
+        let output = FileOutput::create(&path).expect("Could not create output file");
+        let mut interpreter = Interpreter::with_context(Context::with_output(Box::new(output)));
+        let result = interpreter.eval_str("dprint(\"uwu\"); dprint([1, 2]); quwuit(); dprint(3);");
+        drop(interpreter);
+
+        assert!(matches!(result, Err(Error::Terminated(69))));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "uwu\n[1, 2]\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_failed_output() {
+        // Every write to /dev/full fails, which is reported rather than panicking.
+        let output = FileOutput::create("/dev/full").expect("Could not open /dev/full");
+        let mut interpreter = Interpreter::with_context(Context::with_output(Box::new(output)));
+        let result = interpreter.eval_str("dprint(1); 2;");
+        assert!(matches!(
+            result,
+            Err(Error::Runtime(RuntimeError::Custom(message))) if message.starts_with("could not write output")
+        ));
+    }
+
+    #[test]
+    fn test_scripted_input() {
+        let output = BufferOutput::new();
+        let input = ScriptedInput::new(vec!["sai"]);
+        let mut interpreter =
+            Interpreter::with_context(Context::with_io(Box::new(output.clone()), Box::new(input)));
+        let result = interpreter
+            .eval_str("owo name = nya_input(\"name? \"); dprint(name); dprint(nya_input());");
+
+        assert!(result.is_ok());
+        // The prompt has no line break, and running out of input gives none.
+        assert_eq!(output.contents(), "name? sai\nnone\n");
+    }
+
+    #[test]
+    fn test_globals_persist() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_global("a", Object::Integer(4));
+        interpreter.eval_str("owo b = a * 2;").unwrap();
+
+        assert_eq!(interpreter.get_global("b"), Some(Object::Integer(8)));
+        assert_eq!(interpreter.get_global("c"), None);
+        assert_eq!(interpreter.eval_str("a + b;").unwrap(), Object::Integer(12));
+    }
+
+    #[test]
+    fn test_call_function() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .eval_str("uwu add(x, y) { sugoi x + y; };")
+            .unwrap();
+
+        assert_eq!(
+            interpreter
+                .call_function("add", vec![Object::Integer(1), Object::Integer(2)])
+                .unwrap(),
+            Object::Integer(3)
+        );
+        assert_eq!(
+            interpreter
+                .call_function("len", vec![Object::String(String::from("uwu"))])
+                .unwrap(),
+            Object::Integer(3)
+        );
+        assert!(matches!(
+            interpreter.call_function("nope", vec![]),
+            Err(Error::Runtime(RuntimeError::UndefinedVariable(_)))
+        ));
+    }
+
+    #[test]
+    fn test_system_builtins() {
+        // Embedders get no access to the system unless they ask for it.
+        let mut interpreter = Interpreter::new();
+        assert!(matches!(
+            interpreter.eval_str("args();"),
+            Err(Error::Runtime(RuntimeError::UndefinedVariable(_)))
+        ));
+
+        std::env::set_var("UWUCODE_TEST_VAR", "nya");
+        interpreter
+            .context()
+            .set_natives(NativeRegistry::with_system(vec![String::from("-v")]));
+        assert_eq!(
+            interpreter.eval_str("args();").unwrap(),
+            Object::Array(vec![Object::String(String::from("-v"))])
+        );
+        assert_eq!(
+            interpreter
+                .eval_str("env_var(\"UWUCODE_TEST_VAR\");")
+                .unwrap(),
+            Object::String(String::from("nya"))
+        );
+        assert_eq!(
+            interpreter
+                .eval_str("env_var(\"UWUCODE_UNSET_VAR\");")
+                .unwrap(),
+            Object::Null
+        );
+
+        interpreter.context().natives_mut().remove("env_var");
+        assert!(interpreter.eval_str("env_var(\"HOME\");").is_err());
+    }
+
+    #[test]
+    fn test_failed_input_is_rolled_back() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("owo a = 1;").unwrap();
+        assert!(interpreter
+            .eval_str("owo a = 2; owo b = 3; 1 / 0;")
+            .is_err());
+
+        assert_eq!(interpreter.get_global("a"), Some(Object::Integer(1)));
+        assert_eq!(interpreter.get_global("b"), None);
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        let mut interpreter = Interpreter::new();
+        assert!(matches!(
+            interpreter.eval_str("bweak;"),
+            Err(Error::Parse(ParseError::LoopControlOutsideLoop))
+        ));
+        assert!(matches!(
+            interpreter.eval_str("1 / 0;"),
+            Err(Error::Runtime(RuntimeError::DivisionByZero))
+        ));
+    }
+}
@@ -100,4 +100,62 @@ mod tests {
             assert_eq!(test, token);
         }
     }
+
+    #[test]
+    fn test_collections() {
+        let input: &str = "{\"a\": 3}[0]:3";
+
+        let mut test_lexer = lexer::lexer::Lexer::new(input);
+        let tests: TestingStruct = TestingStruct {
+            token_vec: vec![
+                Token::LBRA,
+                Token::STRING(String::from("a")),
+                Token::COLON,
+                Token::INT(3),
+                Token::RBRA,
+                Token::LBRACKET,
+                Token::INT(0),
+                Token::RBRACKET,
+                Token::SEMICOLON,
+            ],
+        };
+        for test in tests.token_vec.iter() {
+            let current_token = test_lexer.next_token();
+            assert_eq!(test, &current_token);
+        }
+    }
+
+    #[test]
+    fn test_unfinished_input() {
+        // Operators and comments at the very end of the input don't need anything after them.
+        let mut test_lexer = lexer::lexer::Lexer::new("1 99999999999999999999 >");
+        assert_eq!(test_lexer.next_token(), Token::INT(1));
+        assert_eq!(
+            test_lexer.next_token(),
+            Token::ILLEGAL(String::from("99999999999999999999"))
+        );
+        assert_eq!(test_lexer.next_token(), Token::GR);
+        assert_eq!(test_lexer.next_token(), Token::EOF);
+
+        let mut test_lexer = lexer::lexer::Lexer::new("owo /* never closed");
+        assert_eq!(test_lexer.next_token(), Token::LET);
+        assert_eq!(test_lexer.next_token(), Token::EOF);
+    }
+
+    #[test]
+    fn test_comments_kept() {
+        let mut test_lexer = lexer::lexer::Lexer::with_comments("owo /* hi */ x /* open");
+        assert_eq!(test_lexer.next_token(), Token::LET);
+        assert_eq!(
+            test_lexer.next_token(),
+            Token::COMMENT(String::from(" hi "))
+        );
+        assert_eq!(test_lexer.span(), 4..12);
+        assert_eq!(test_lexer.next_token(), Token::IDENT(String::from("x")));
+        assert_eq!(
+            test_lexer.next_token(),
+            Token::COMMENT(String::from(" open"))
+        );
+        assert_eq!(test_lexer.next_token(), Token::EOF);
+    }
 }
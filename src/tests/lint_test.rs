@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use crate::analysis::diagnostic::line_col;
+    use crate::analysis::lint::{lint, RULES};
+    use crate::parser::cst::parse_cst;
+
+    /// Lints source, returning the rule and line of each lint.
+    fn lint_input(source: &str) -> Vec<(&'static str, usize)> {
+        lint(&parse_cst(source).unwrap(), source)
+            .iter()
+            .map(|lint| (lint.rule.id, line_col(source, lint.span.start).0))
+            .collect()
+    }
+
+    #[test]
+    fn test_examples_lint() {
+        for entry in std::fs::read_dir("examples").unwrap() {
+            let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            assert_eq!(lint_input(&source), vec![]);
+        }
+    }
+
+    #[test]
+    fn test_rule_ids_unique() {
+        for (index, rule) in RULES.iter().enumerate() {
+            assert!(RULES[index + 1..].iter().all(|other| other.id != rule.id));
+        }
+    }
+
+    #[test]
+    fn test_unused_and_unreachable() {
+        assert_eq!(
+            lint_input("owo x = 1;\nowo y = 2;\nuwu f() {\n    sugoi y;\n    dprint(y);\n};"),
+            vec![("unused-binding", 1), ("unreachable-code", 5)]
+        );
+    }
+
+    #[test]
+    fn test_conditions() {
+        assert_eq!(
+            lint_input("nuzzles (1 == 1) {};\nnyaa (truwu) { bweak; };\nnyaa (fowose) {};"),
+            vec![
+                ("constant-condition", 1),
+                ("self-comparison", 1),
+                ("constant-condition", 3),
+            ]
+        );
+        assert_eq!(
+            lint_input("owo i = 0;\nnyaa (i < 3) { dprint(i); };\nnyaa (i < 3) { owo i = i + 1; };\nnyaa (i < len(i)) {};"),
+            vec![("unchanging-loop", 2)]
+        );
+        assert_eq!(
+            lint_input("fur (x in [1]) {\nnyaa (nuzzles (x == 1) { bweak; } rawr { fowose; }) {};\n};\nuwu f(x) { nyaa (nuzzles (x) { sugoi 1; } rawr { truwu; }) {}; };"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_mixed_terminators() {
+        assert_eq!(
+            lint_input("dprint(1);\ndprint(2) :3\ndprint(3) :3"),
+            vec![("mixed-terminators", 2)]
+        );
+    }
+
+    #[test]
+    fn test_allow_comments() {
+        let source = "/* lint: allow(unused-binding, self-comparison) */\nowo x = 1 == 1;\nowo y = 2; /* lint: allow(unused-binding) */\nowo z = 3;";
+        assert_eq!(lint_input(source), vec![]);
+        assert_eq!(
+            lint_input("/* lint: allow(unused-binding) */\n\nowo x = 1;"),
+            vec![("unused-binding", 3)]
+        );
+    }
+}
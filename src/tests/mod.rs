@@ -1,3 +1,16 @@
 //! Self explanatory.
+mod arity_test;
+mod cli_test;
+mod cst_test;
+mod differential_test;
+mod eval_test;
+mod formatter_test;
+mod interpreter_test;
 mod lexer_test;
+mod lint_test;
+mod parser_test;
+mod repl_test;
+mod resolver_test;
 mod token_test;
+mod uwuc_test;
+mod vm_test;
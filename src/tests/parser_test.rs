@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests {
+    use crate::lexer::lexer::Lexer;
+    use crate::parser::ast::{fmt_program, Expr, Statement};
+    use crate::parser::error::ParseError;
+    use crate::parser::parser::parse;
+    use crate::token::token::Token;
+
+    fn parse_input(input: &str) -> Result<Vec<Statement>, ParseError> {
+        let mut token_vec = Lexer::new(input).lex();
+        parse(&mut token_vec)
+    }
+
+    #[test]
+    fn test_loop_control() {
+        let parsed = parse_input("nyaa (truwu) { bweak; continyue; };");
+        assert_eq!(
+            parsed,
+            Ok(vec![Statement::Expression(Expr::While {
+                condition: Box::new(Expr::Boolean(true)),
+                instruction: vec![Statement::Break, Statement::Continue],
+            })])
+        );
+    }
+
+    #[test]
+    fn test_break_outside_loop() {
+        assert_eq!(
+            parse_input("nuzzles (truwu) { bweak; };"),
+            Err(ParseError::LoopControlOutsideLoop)
+        );
+    }
+
+    #[test]
+    fn test_continue_in_function_inside_loop() {
+        assert_eq!(
+            parse_input("nyaa (truwu) { uwu f() { continyue; }; };"),
+            Err(ParseError::LoopControlOutsideLoop)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_input("owo = 3;"),
+            Err(ParseError::unexpected(
+                "a variable name",
+                Some(Token::ASSIGN)
+            ))
+        );
+        assert_eq!(
+            parse_input("dprint(1"),
+            Err(ParseError::unexpected(", or )", Some(Token::EOF)))
+        );
+        assert_eq!(parse_input("3 = 4;"), Err(ParseError::InvalidAssignment));
+        assert_eq!(
+            parse_input("owo x = 3 $;"),
+            Err(ParseError::IllegalToken(String::from("$")))
+        );
+        assert_eq!(
+            parse_input("};"),
+            Err(ParseError::unexpected("a statement", Some(Token::RBRA)))
+        );
+    }
+
+    /// Parses, prints and parses again, checking the tree is unchanged.
+    fn assert_round_trip(input: &str) -> String {
+        let parsed = parse_input(input).expect("Parsing failed");
+        let printed = fmt_program(&parsed);
+        assert_eq!(
+            parse_input(&printed),
+            Ok(parsed),
+            "{} printed as {}",
+            input,
+            printed
+        );
+        printed
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(assert_round_trip("owo x=1+2*3 :3"), "owo x = 1 + 2 * 3;\n");
+        assert_eq!(assert_round_trip("(1 + 2) * 3;"), "(1 + 2) * 3;\n");
+        assert_eq!(assert_round_trip("1 - (2 - 3);"), "1 - (2 - 3);\n");
+        assert_eq!(assert_round_trip("(1 - 2) - 3;"), "1 - 2 - 3;\n");
+        assert_eq!(assert_round_trip("-(a + 1);"), "-(a + 1);\n");
+        assert_eq!(
+            assert_round_trip("!truwu == fowose;"),
+            "!truwu == fowose;\n"
+        );
+        assert_eq!(assert_round_trip("(-a)[0];"), "(-a)[0];\n");
+        assert_eq!(
+            assert_round_trip("uwu f(a,b){nuzzles(a<b){sugoi a;}rawr{};};"),
+            "uwu f(a, b) { nuzzles (a < b) { sugoi a; }; };\n"
+        );
+        assert_eq!(
+            assert_round_trip("owo m = {\"a\": [1, 2]}; m[\"a\"] = {}; len(m);"),
+            "owo m = {\"a\": [1, 2]};\nm[\"a\"] = {};\nlen(m);\n"
+        );
+        assert_round_trip("fur (x in range(3)) { nuzzles (x == 1) { continyue; }; bweak; };");
+        assert_round_trip("nyaa (i < 10) { owo i = i + 1; };");
+    }
+
+    #[test]
+    fn test_examples_round_trip() {
+        for entry in std::fs::read_dir("examples").unwrap() {
+            let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            assert_round_trip(&source);
+        }
+    }
+}
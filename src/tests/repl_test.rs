@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::interpreter::Interpreter;
+    use crate::repl::commands::{is_command, run_command, CommandResult};
+    use crate::repl::helper::{highlight, ReplHelper};
+    use crate::repl::repl::is_incomplete;
+    use crate::token::token::KEYWORDS;
+    use colored::*;
+
+    #[test]
+    fn test_complete_input() {
+        assert!(!is_incomplete("owo x = 3 :3\n"));
+        assert!(!is_incomplete("uwu f(x) {\n sugoi x;\n};\n"));
+        assert!(!is_incomplete("dprint(\"{\");\n"));
+        assert!(!is_incomplete("\n"));
+    }
+
+    #[test]
+    fn test_incomplete_input() {
+        assert!(is_incomplete("uwu f(x) {\n"));
+        assert!(is_incomplete("dprint(1,\n"));
+        assert!(is_incomplete("owo xs = [1, 2\n"));
+        assert!(is_incomplete("dprint(\"uwu\n"));
+        assert!(is_incomplete("/* a comment\n"));
+        // The statement has not been terminated yet.
+        assert!(is_incomplete("owo x = 3\n"));
+    }
+
+    #[test]
+    fn test_errors_are_not_incomplete() {
+        // These can't be fixed by typing more, so they are reported straight away.
+        assert!(!is_incomplete("};\n"));
+        assert!(!is_incomplete("owo = 3;\n"));
+    }
+
+    #[test]
+    fn test_is_command() {
+        assert!(is_command(":help\n"));
+        assert!(is_command("  :ast 1;\n"));
+        // :3 ends a statement rather than starting a command.
+        assert!(!is_command(":3\n"));
+        assert!(!is_command("owo x = 1 :3\n"));
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .eval_str("owo s = \"uwu\"; owo n = 2; uwu f(a, b) { sugoi a; };")
+            .unwrap();
+
+        assert_eq!(
+            run_command(":env", &mut interpreter),
+            CommandResult::Continue(String::from("uwu f(a, b)\nowo n = 2\nowo s = \"uwu\""))
+        );
+        assert_eq!(
+            run_command(":tokens n;", &mut interpreter),
+            CommandResult::Continue(String::from("IDENT(\"n\")\nSEMICOLON\nEOF"))
+        );
+        assert_eq!(
+            run_command(":ast n;", &mut interpreter),
+            CommandResult::Continue(String::from(
+                "Expression(\n    Variable(\n        \"n\",\n    ),\n)"
+            ))
+        );
+
+        run_command(":reset", &mut interpreter);
+        assert_eq!(interpreter.get_global("n"), None);
+        assert_eq!(run_command(":quit", &mut interpreter), CommandResult::Quit);
+    }
+
+    #[test]
+    fn test_commands_pass_on_quwuit() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(
+            run_command(":time quwuit(7);", &mut interpreter),
+            CommandResult::Exit(7)
+        );
+
+        let path = std::env::temp_dir().join(format!("uwucode_load_{}.uwu", std::process::id()));
+        std::fs::write(&path, "quwuit(3);").unwrap();
+        let result = run_command(&format!(":load {}", path.display()), &mut interpreter);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(result, CommandResult::Exit(3));
+    }
+
+    #[test]
+    fn test_completion() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .eval_str("owo nyan = 1; owo nyaaaa = 2;")
+            .unwrap();
+        let helper = ReplHelper::new(&interpreter);
+
+        // Keywords, builtins and bindings are all completed.
+        assert_eq!(
+            helper.complete_word("dprint(ny", 9),
+            (
+                7,
+                vec![
+                    String::from("nya_input"),
+                    String::from("nyaa"),
+                    String::from("nyaaaa"),
+                    String::from("nyan")
+                ]
+            )
+        );
+        assert_eq!(
+            helper.complete_word("nuz", 3),
+            (0, vec![String::from("nuzzles")])
+        );
+        assert_eq!(
+            helper.complete_word("owo x = le", 10),
+            (8, vec![String::from("len")])
+        );
+        assert_eq!(helper.complete_word("1 + 2", 5), (5, vec![]));
+        assert_eq!(helper.complete_word("owo x = ", 8), (8, vec![]));
+    }
+
+    #[test]
+    fn test_highlighting() {
+        colored::control::set_override(true);
+        let expected = format!(
+            "{} x {} {} {} {}{} {}",
+            "owo".magenta().bold(),
+            "=".blue(),
+            "\"uwu\"".green(),
+            "/* nya */".bright_black(),
+            "3".yellow(),
+            ";".normal(),
+            "$".white().on_red(),
+        );
+        assert_eq!(highlight("owo x = \"uwu\" /* nya */ 3; $", None), expected);
+        for keyword in KEYWORDS {
+            assert_eq!(
+                highlight(keyword, None),
+                keyword.magenta().bold().to_string()
+            );
+        }
+        // Highlighting never changes the text itself, even if it can't be lexed yet.
+        assert!(highlight("owo x =", None).contains("owo"));
+        assert_eq!(
+            highlight("/* unclosed", None),
+            "/* unclosed".bright_black().to_string()
+        );
+    }
+
+    #[test]
+    fn test_bracket_matching() {
+        colored::control::set_override(true);
+        let open = "(".bright_cyan().bold().underline().to_string();
+        let close = ")".bright_cyan().bold().underline().to_string();
+
+        // The cursor is just after the closing parenthesis, and the one inside the string is skipped.
+        let line = "f(\")\")";
+        let highlighted = highlight(line, Some(line.len()));
+        assert!(highlighted.starts_with(&format!("f{}", open)));
+        assert!(highlighted.ends_with(&close));
+
+        let unmatched = highlight("f(1", Some(1));
+        assert!(unmatched.contains(&"(".red().bold().to_string()));
+        assert!(!highlight("f(1)", None).contains(&open));
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use crate::analysis::diagnostic::Severity;
+    use crate::analysis::resolver::{resolve, NameDiagnostic, NameProblem};
+    use crate::parser::cst::parse_cst;
+
+    fn diagnose(input: &str) -> Vec<NameDiagnostic> {
+        let tree = parse_cst(input).unwrap();
+        resolve(&tree, &[String::from("dprint"), String::from("len")])
+    }
+
+    fn resolve_input(input: &str) -> Vec<NameProblem> {
+        diagnose(input)
+            .into_iter()
+            .map(|diagnostic| diagnostic.problem)
+            .collect()
+    }
+
+    fn name(name: &str) -> String {
+        String::from(name)
+    }
+
+    #[test]
+    fn test_examples_resolve() {
+        for entry in std::fs::read_dir("examples").unwrap() {
+            let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            assert_eq!(resolve_input(&source), vec![]);
+        }
+    }
+
+    #[test]
+    fn test_undefined() {
+        assert_eq!(
+            resolve_input("dprint(x); x; m[1] = 2;"),
+            vec![
+                NameProblem::Undefined(name("x")),
+                NameProblem::Undefined(name("m"))
+            ]
+        );
+        // The loop variable does not outlive the loop.
+        assert_eq!(
+            resolve_input("fur (i in [1]) { dprint(i); }; i;"),
+            vec![NameProblem::Undefined(name("i"))]
+        );
+    }
+
+    #[test]
+    fn test_used_before_definition() {
+        assert_eq!(
+            resolve_input("f(); uwu f() { sugoi g(); }; uwu g() { sugoi 1; };"),
+            vec![NameProblem::UsedBeforeDefinition(name("f"))]
+        );
+        assert_eq!(
+            resolve_input("uwu f() { dprint(y); owo y = 1; };"),
+            vec![NameProblem::UsedBeforeDefinition(name("y"))]
+        );
+        // Blocks define into the scope around them.
+        assert_eq!(
+            resolve_input("nuzzles (truwu) { owo x = 1; }; nyaa (fowose) { owo y = x; }; y;"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_parameters() {
+        assert_eq!(
+            resolve_input("uwu f(a, b, a) { sugoi a; };"),
+            vec![NameProblem::DuplicateParameter {
+                function: name("f"),
+                parameter: name("a"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_shadowing() {
+        let problems = resolve_input(
+            "owo x = 1; owo x = 2; uwu f(y) { owo x = y; fur (y in [1]) {}; sugoi x; }; owo len = 0;",
+        );
+        assert_eq!(
+            problems,
+            vec![
+                NameProblem::Shadowed(name("x")),
+                NameProblem::Shadowed(name("y")),
+                NameProblem::Shadowed(name("len")),
+            ]
+        );
+        assert!(problems
+            .iter()
+            .all(|problem| problem.severity() == Severity::Warning));
+    }
+
+    #[test]
+    fn test_defined_by_caller() {
+        // Calls run in the caller's scope, so f can see the y that g defines.
+        let problems =
+            resolve_input("uwu f() { sugoi y; }; uwu g() { owo y = 1; sugoi f(); }; g();");
+        assert_eq!(problems, vec![NameProblem::DefinedByCaller(name("y"))]);
+        assert_eq!(problems[0].severity(), Severity::Warning);
+        // Names nothing binds are still undefined, inside of a function or not.
+        assert_eq!(
+            resolve_input("uwu f() { sugoi z; };"),
+            vec![NameProblem::Undefined(name("z"))]
+        );
+    }
+
+    #[test]
+    fn test_spans() {
+        let source = "owo x = y; owo len = x;\nuwu f(a, a) {};";
+        let spans: Vec<&str> = diagnose(source)
+            .iter()
+            .map(|diagnostic| &source[diagnostic.span.clone()])
+            .collect();
+        assert_eq!(spans, vec!["y", "len", "a"]);
+    }
+}
@@ -8,7 +8,19 @@ mod tests {
             token_vec: Vec<Token>,
         }
         let inputs: Vec<&str> = vec![
-            "uwu", "owo", "nuzzles", "dab", "rawr", "sugoi", "truwu", "fowose", "Hello!",
+            "uwu",
+            "owo",
+            "nuzzles",
+            "dab",
+            "rawr",
+            "sugoi",
+            "truwu",
+            "fowose",
+            "fur",
+            "in",
+            "bweak",
+            "continyue",
+            "Hello!",
         ];
 
         let tests: TestingStruct = TestingStruct {
@@ -21,6 +33,10 @@ mod tests {
                 Token::RETURN,
                 Token::TRUE,
                 Token::FALSE,
+                Token::FOR,
+                Token::IN,
+                Token::BREAK,
+                Token::CONTINUE,
                 Token::IDENT(String::from("Hello!")),
             ],
         };
@@ -31,4 +47,11 @@ mod tests {
             assert_eq!(&lookup_ident(input), test);
         }
     }
+
+    #[test]
+    fn test_keywords_are_looked_up() {
+        for keyword in KEYWORDS {
+            assert!(!matches!(lookup_ident(keyword), Token::IDENT(_)));
+        }
+    }
 }
@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod tests {
+    use crate::eval::context::Context;
+    use crate::eval::eval::Object;
+    use crate::eval::output::BufferOutput;
+    use crate::interpreter::interpreter::Interpreter;
+    use crate::vm::bytecode::{line_at, Instruction};
+    use crate::vm::compiler::compile_source;
+    use crate::vm::disasm::disassemble;
+    use crate::vm::error::LoadError;
+    use crate::vm::uwuc::{decode, encode, is_compiled, VERSION};
+    use std::fs;
+
+    #[test]
+    fn test_round_trip() {
+        let source = fs::read_to_string("examples/rec_fizzbuzz.uwu").unwrap();
+        let program = compile_source(&source).unwrap();
+        assert!(program.has_lines());
+        let bytes = encode(&program);
+        assert!(is_compiled(&bytes));
+        assert_eq!(decode(&bytes), Ok(program.clone()));
+
+        let mut stripped = program;
+        stripped.strip_lines();
+        let stripped_bytes = encode(&stripped);
+        assert!(stripped_bytes.len() < bytes.len());
+        assert_eq!(decode(&stripped_bytes), Ok(stripped));
+    }
+
+    #[test]
+    fn test_run_compiled() {
+        let source = "uwu twice(x) { sugoi x * 2; }; dprint(twice(4)); {\"a\": [1, -2]};";
+        let bytes = encode(&compile_source(source).unwrap());
+        let output = BufferOutput::new();
+        let mut interpreter =
+            Interpreter::with_context(Context::with_output(Box::new(output.clone())));
+        let value = interpreter.eval_program(&bytes).unwrap();
+        assert_eq!(value.to_string(), "{\"a\": [1, -2]}");
+        assert_eq!(output.contents(), "8\n");
+        // Functions defined by a compiled program can be called later on.
+        assert_eq!(
+            interpreter.eval_str("twice(5);").unwrap(),
+            Object::Integer(10)
+        );
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let bytes = encode(&compile_source("owo x = 1; x;").unwrap());
+        assert_eq!(decode(b"owo x = 1;"), Err(LoadError::NotCompiled));
+        assert_eq!(decode(&bytes[..bytes.len() - 1]), Err(LoadError::Truncated));
+
+        let mut newer = bytes.clone();
+        newer[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert_eq!(
+            decode(&newer),
+            Err(LoadError::UnsupportedVersion(VERSION + 1))
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(decode(&trailing), Err(LoadError::Malformed(_))));
+
+        let mut program = compile_source("owo x = 1;").unwrap();
+        program.code.insert(0, Instruction::Jump(99));
+        assert!(matches!(
+            decode(&encode(&program)),
+            Err(LoadError::Malformed(_))
+        ));
+        program.code[0] = Instruction::Load(0);
+        assert!(matches!(
+            decode(&encode(&program)),
+            Err(LoadError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_unbalanced_code() {
+        let load = |code: Vec<Instruction>| {
+            let mut program = compile_source("owo x = 1;").unwrap();
+            program.code = code;
+            decode(&encode(&program))
+        };
+        let malformed = [
+            vec![
+                Instruction::Pop,
+                Instruction::Pop,
+                Instruction::True,
+                Instruction::Return,
+            ],
+            vec![Instruction::Null, Instruction::Break, Instruction::Return],
+            vec![Instruction::Continue, Instruction::Return],
+            vec![
+                Instruction::ExitScope,
+                Instruction::Null,
+                Instruction::Return,
+            ],
+            vec![
+                Instruction::ExitLoop,
+                Instruction::Null,
+                Instruction::Return,
+            ],
+            // Next needs the items and position Iterate leaves.
+            vec![
+                Instruction::Null,
+                Instruction::Null,
+                Instruction::Next {
+                    variable: 0,
+                    exit: 3,
+                },
+                Instruction::Return,
+            ],
+            // Each time round this loop the stack grows.
+            vec![
+                Instruction::Null,
+                Instruction::True,
+                Instruction::JumpUnlessTrue(4),
+                Instruction::Jump(0),
+                Instruction::Null,
+                Instruction::Return,
+            ],
+            vec![
+                Instruction::Call {
+                    arguments: 2,
+                    name: 0,
+                },
+                Instruction::Return,
+            ],
+        ];
+        for code in malformed {
+            assert!(
+                matches!(load(code.clone()), Err(LoadError::Malformed(_))),
+                "{:?} loaded",
+                code
+            );
+        }
+
+        let mut program = compile_source("uwu f() { sugoi 1; };").unwrap();
+        program.functions[0].code.insert(0, Instruction::Pop);
+        assert!(matches!(
+            decode(&encode(&program)),
+            Err(LoadError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_lines() {
+        let source = "owo x = 1;\nuwu f(y) {\n    sugoi y;\n};\nnuzzles (x == 1) {\n    f(x);\n};";
+        let program = compile_source(source).unwrap();
+        assert_eq!(line_at(&program.lines, 0), Some(1));
+        assert_eq!(line_at(&program.functions[0].lines, 0), Some(3));
+        let call = program
+            .code
+            .iter()
+            .position(|instruction| matches!(instruction, Instruction::Call { .. }))
+            .unwrap();
+        assert_eq!(line_at(&program.lines, call), Some(6));
+        // The end of the nuzzles belongs to its statement rather than the last one in its block.
+        assert_eq!(line_at(&program.lines, program.code.len() - 1), Some(5));
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let listing = disassemble(&compile_source("uwu f(a) { sugoi a; };\nf(\"b\");").unwrap());
+        assert!(listing.starts_with("constants:\n     0  \"a\"\n"));
+        assert!(listing.contains("\nmain:\n  line 1\n"));
+        assert!(listing.contains("Call 1 1  ; f"));
+        assert!(listing.contains("\nfunction 0 f(a):\n"));
+    }
+}
@@ -0,0 +1,203 @@
+#[cfg(test)]
+mod tests {
+    use crate::eval::context::Context;
+    use crate::eval::eval::{Object, RuntimeError};
+    use crate::eval::output::BufferOutput;
+    use crate::interpreter::error::Error;
+    use crate::interpreter::interpreter::{Backend, Interpreter};
+    use crate::lexer::lexer::Lexer;
+    use crate::parser::parser::parse;
+    use crate::vm::bytecode::Instruction;
+    use crate::vm::compiler::compile;
+
+    fn run(source: &str, backend: Backend) -> (Result<Object, Error>, String) {
+        let output = BufferOutput::new();
+        let mut ctx = Context::with_output(Box::new(output.clone()));
+        // Deep recursion would overflow the test thread's stack on the tree-walker.
+        ctx.set_max_call_depth(50);
+        let mut interpreter = Interpreter::with_context(ctx);
+        interpreter.set_backend(backend);
+        let result = interpreter.eval_str(source);
+        (result, output.contents())
+    }
+
+    /// Runs a program on both backends, checking they agree, and returns what the VM gave. Functions are different objects on each backend, so values are compared by how they print.
+    fn run_both(source: &str) -> (Result<String, Error>, String) {
+        let (tree, tree_output) = run(source, Backend::TreeWalker);
+        let (vm, vm_output) = run(source, Backend::Vm);
+        let tree = tree.map(|value| value.to_string());
+        let vm = vm.map(|value| value.to_string());
+        assert_eq!(
+            format!("{:?}", tree),
+            format!("{:?}", vm),
+            "results differ for {}",
+            source
+        );
+        assert_eq!(tree_output, vm_output, "output differs for {}", source);
+        (vm, vm_output)
+    }
+
+    fn value(source: &str) -> String {
+        run_both(source).0.expect("Evaluation was interrupted")
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(value("1 + 2 * 3;"), "7");
+        assert_eq!(value("owo x = 5; -x;"), "-5");
+        assert_eq!(value("!fowose;"), "truwu");
+        assert_eq!(value("\"a\" == \"a\";"), "truwu");
+        assert_eq!(value("[1, \"b\", [truwu]];"), "[1, \"b\", [truwu]]");
+        assert_eq!(value("{\"a\": 1, 2: 3};"), "{2: 3, \"a\": 1}");
+        assert_eq!(value("[1, 2, 3][1];"), "2");
+        assert_eq!(
+            value("owo m = {\"a\": 1}; m[\"b\"] = 2; m;"),
+            "{\"a\": 1, \"b\": 2}"
+        );
+        assert_eq!(value(""), "none");
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(
+            value(
+                "uwu fact(n) { nuzzles (n < 2) { sugoi 1; }; sugoi n * fact(n - 1); }; fact(10);"
+            ),
+            "3628800"
+        );
+        assert_eq!(value("uwu f(a, b) { a - b; }; f(5, 3);"), "2");
+        assert_eq!(value("uwu f(x) { sugoi x; };"), "uwu(x) { sugoi x; }");
+        assert_eq!(value("owo x = 1; uwu f() { x; }; owo x = 2; f();"), "2");
+        assert_eq!(value("len(\"uwu\");"), "3");
+    }
+
+    #[test]
+    fn test_loops() {
+        let source = "owo total = 0;
+        owo i = 0;
+        nyaa (i < 10) {
+            owo i = i + 1;
+            nuzzles (i % 2 == 0) { continyue; };
+            nuzzles (i > 7) { bweak; };
+            owo total = total + i;
+        };
+        total;";
+        assert_eq!(value(source), "16");
+
+        let source = "owo seen = 0;
+        fur (c in \"uwu!\") {
+            nuzzles (c == \"!\") { bweak; };
+            nuzzles (c == \"w\") { continyue; };
+            owo seen = seen + 1;
+        };
+        seen;";
+        assert_eq!(value(source), "2");
+
+        assert_eq!(
+            value("owo n = 0; fur (k in {1: 2, 3: 4}) { owo n = n + k; }; n;"),
+            "4"
+        );
+        assert_eq!(value("fur (x in [1, 2]) { x; };"), "none");
+    }
+
+    #[test]
+    fn test_break_drops_partial_values() {
+        let source = "owo out = [];
+        fur (x in [1, 2, 3]) {
+            owo out = [x, nuzzles (x == 2) { bweak; }];
+        };
+        out;";
+        assert_eq!(value(source), "[1, none]");
+    }
+
+    #[test]
+    fn test_return_from_loops() {
+        let source = "uwu find(items, target) {
+            fur (item in items) {
+                nyaa (truwu) {
+                    nuzzles (item == target) { sugoi item * 10; };
+                    bweak;
+                };
+            };
+            sugoi 0;
+        };
+        [find([1, 2, 3], 2), find([1], 5)];";
+        assert_eq!(value(source), "[20, 0]");
+        assert_eq!(value("fur (x in [1, 2]) { sugoi x; }; 5;"), "1");
+    }
+
+    #[test]
+    fn test_globals_persist() {
+        let mut interpreter =
+            Interpreter::with_context(Context::with_output(Box::new(BufferOutput::new())));
+        interpreter.set_backend(Backend::Vm);
+        interpreter
+            .eval_str("fur (x in [1]) { owo y = x; sugoi x; };")
+            .unwrap();
+        assert_eq!(interpreter.get_global("y"), Some(Object::Integer(1)));
+        assert_eq!(interpreter.get_global("x"), None);
+        interpreter.eval_str("uwu f(n) { sugoi n + 1; };").unwrap();
+        assert_eq!(interpreter.eval_str("f(y);").unwrap(), Object::Integer(2));
+    }
+
+    #[test]
+    fn test_output() {
+        let (_, output) = run_both("dprint(\"a\"); uwu f(x) { dprint(x); }; f(1); f([2]);");
+        assert_eq!(output, "a\n1\n[2]\n");
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source| match run_both(source).0 {
+            Err(Error::Runtime(error)) => error,
+            result => panic!("expected an error, got {:?}", result),
+        };
+        assert_eq!(error("1 / 0;"), RuntimeError::DivisionByZero);
+        assert_eq!(
+            error("x;"),
+            RuntimeError::UndefinedVariable(String::from("x"))
+        );
+        assert!(matches!(
+            error("uwu f(x) {}; f();"),
+            RuntimeError::WrongArity { .. }
+        ));
+        assert!(matches!(error("len();"), RuntimeError::WrongArity { .. }));
+        assert!(matches!(
+            error("owo x = 1; x();"),
+            RuntimeError::TypeMismatch(_)
+        ));
+        assert!(matches!(
+            error("fur (x in 1) {};"),
+            RuntimeError::TypeMismatch(_)
+        ));
+        assert!(matches!(
+            error("[1][5];"),
+            RuntimeError::IndexOutOfRange { .. }
+        ));
+        assert!(matches!(
+            error("uwu f() { f(); }; f();"),
+            RuntimeError::CallDepthExceeded(_)
+        ));
+        // Keys are checked before their value is evaluated.
+        assert!(matches!(
+            error("owo k = [1]; {k: 1 / 0};"),
+            RuntimeError::TypeMismatch(_)
+        ));
+        // Arguments are evaluated before the callee is looked up.
+        assert_eq!(error("missing(1 / 0);"), RuntimeError::DivisionByZero);
+    }
+
+    #[test]
+    fn test_terminate() {
+        let (result, output) = run_both("dprint(1); quwuit(3); dprint(2);");
+        assert!(matches!(result, Err(Error::Terminated(3))));
+        assert_eq!(output, "1\n");
+    }
+
+    #[test]
+    fn test_constant_pool() {
+        let program = compile(&parse(&mut Lexer::new("owo a = 7; a + 7; \"a\";").lex()).unwrap());
+        assert_eq!(program.constants.len(), 2);
+        assert_eq!(program.code.last(), Some(&Instruction::Return));
+    }
+}
@@ -1,15 +1,32 @@
+/// Every word lookup_ident turns into a keyword rather than an identifier.
+pub const KEYWORDS: &[&str] = &[
+    "uwu",
+    "owo",
+    "nuzzles",
+    "dab",
+    "rawr",
+    "sugoi",
+    "truwu",
+    "fowose",
+    "nyaa",
+    "fur",
+    "in",
+    "bweak",
+    "continyue",
+];
+
 ///
 /// Maps an identifier to a keyword or a variable/function.
 /// # Examples
 ///
 /// Various keywords are mapped to a respective action.
-/// ```
+/// ```ignore
 /// let result = lookup_ident("uwu"); // Returns Token::FUNCTION
 /// assert_eq!(Token::FUNCTION,result);
 /// ```
 ///
 /// If the keyword is not recognized, it gets mapped to an identifier.
-/// ```
+/// ```ignore
 /// let result = lookup_ident("random_thing");
 /// assert_eq!(Token::IDENT(String::from("random_thing")),result);
 /// ```
@@ -26,6 +43,10 @@ pub fn lookup_ident(ident: &str) -> Token {
         "truwu" => Token::TRUE,
         "fowose" => Token::FALSE,
         "nyaa" => Token::WHILE,
+        "fur" => Token::FOR,
+        "in" => Token::IN,
+        "bweak" => Token::BREAK,
+        "continyue" => Token::CONTINUE,
         _ => Token::IDENT(String::from(ident)),
     }
 }
@@ -36,6 +57,8 @@ pub fn lookup_ident(ident: &str) -> Token {
 pub enum Token {
     ILLEGAL(String),
     EOF,
+    /// Only produced by lexers that keep comments, holding the text between /* and */.
+    COMMENT(String),
 
     IDENT(String),
 
@@ -55,11 +78,14 @@ pub enum Token {
 
     COMMA,
     SEMICOLON,
+    COLON,
 
     LPAR,
     RPAR,
     LBRA,
     RBRA,
+    LBRACKET,
+    RBRACKET,
 
     BANG,
 
@@ -72,6 +98,10 @@ pub enum Token {
     ELSE,
 
     WHILE,
+    FOR,
+    IN,
+    BREAK,
+    CONTINUE,
 
     INT(i64),
     STRING(String),
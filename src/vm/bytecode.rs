@@ -0,0 +1,169 @@
+//! The instructions the compiler emits and the program they are grouped into.
+use crate::parser::ast::{Operator, Prefix};
+use std::fmt;
+use std::rc::Rc;
+
+/// A literal stored once in the constant pool and referred to by position. Names are constants too.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Integer(i64),
+    String(String),
+}
+
+/// One step of the VM. Operands are positions in the constant pool, the function table or the code being run.
+///
+/// # Technical Information
+/// Every statement leaves exactly one value on the stack, which is how the value of the last statement becomes the value of a block.
+/// Loops push a record of the stack height when entered, so bweak and continyue can drop whatever a half evaluated expression left behind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    /// Pushes a constant.
+    Constant(u32),
+    True,
+    False,
+    Null,
+    Pop,
+    /// Pushes the value of the named variable, or the native of that name.
+    Load(u32),
+    /// Binds the value on top of the stack to a name in the current scope, leaving it on the stack.
+    Store(u32),
+    /// Pops a value and an index and assigns the value to that index of the named variable, pushing the value back.
+    SetIndex(u32),
+    Index,
+    /// Checks that the value on top of the stack can be used as a map key.
+    MapKey,
+    /// Collects the given number of values into an array.
+    Array(u32),
+    /// Collects the given number of key value pairs into a map.
+    Map(u32),
+    Prefix(Prefix),
+    Infix(Operator),
+    Jump(u32),
+    /// Pops a condition and jumps unless it is truwu.
+    JumpUnlessTrue(u32),
+    /// Pushes a function from the function table.
+    Function(u32),
+    /// Pops a callee and then its arguments. The name is only used in errors.
+    Call {
+        arguments: u32,
+        name: u32,
+    },
+    /// Leaves the current function, or the program, with the value on top of the stack.
+    Return,
+    /// Starts a loop, recording where bweak and continyue jump to.
+    EnterLoop {
+        exit: u32,
+        next: u32,
+    },
+    ExitLoop,
+    Break,
+    Continue,
+    /// Replaces a collection with the items fur loops over and the position of the next one.
+    Iterate,
+    /// Opens the scope of a fur loop that owns the named variable.
+    EnterScope(u32),
+    /// Binds the next item to the loop variable, or jumps once there are none left.
+    Next {
+        variable: u32,
+        exit: u32,
+    },
+    ExitScope,
+}
+
+/// A function body compiled on its own, so it can be called from anywhere.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub parameters: Vec<String>,
+    /// How the function is printed, which matches the tree-walker.
+    pub display: String,
+    pub code: Vec<Instruction>,
+    pub lines: LineTable,
+}
+
+/// Everything the compiler produces for one source. Functions refer to constants and other functions by position, so they are only meaningful within their program.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Program {
+    pub constants: Vec<Constant>,
+    pub functions: Vec<Function>,
+    /// The top level of the program.
+    pub code: Vec<Instruction>,
+    pub lines: LineTable,
+}
+
+/// Debug information mapping instructions back to source lines. Each entry gives the line of every instruction from its position up to the next entry's, and an empty table means there is none.
+pub type LineTable = Vec<(u32, u32)>;
+
+/// Finds the source line an instruction came from, if the table says.
+#[cfg(test)]
+pub fn line_at(lines: &[(u32, u32)], position: usize) -> Option<u32> {
+    lines
+        .iter()
+        .take_while(|(start, _)| *start as usize <= position)
+        .last()
+        .map(|(_, line)| *line)
+}
+
+impl Program {
+    /// Looks up a constant that the compiler stored as a name.
+    pub fn name(&self, index: u32) -> &str {
+        match &self.constants[index as usize] {
+            Constant::String(name) => name,
+            constant => panic!("constant {:?} is not a name", constant),
+        }
+    }
+
+    /// Drops the line tables, leaving only what is needed to run the program.
+    pub fn strip_lines(&mut self) {
+        self.lines.clear();
+        for function in &mut self.functions {
+            function.lines.clear();
+        }
+    }
+
+    pub fn has_lines(&self) -> bool {
+        !self.lines.is_empty()
+            || self
+                .functions
+                .iter()
+                .any(|function| !function.lines.is_empty())
+    }
+}
+
+/// A function value created by the VM, which keeps the program it was compiled in alive.
+#[derive(Clone)]
+pub struct CompiledFunction {
+    program: Rc<Program>,
+    index: usize,
+}
+
+impl CompiledFunction {
+    pub(crate) fn new(program: Rc<Program>, index: usize) -> Self {
+        CompiledFunction { program, index }
+    }
+
+    pub(crate) fn program(&self) -> &Rc<Program> {
+        &self.program
+    }
+
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+
+    pub(crate) fn function(&self) -> &Function {
+        &self.program.functions[self.index]
+    }
+}
+
+impl fmt::Debug for CompiledFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CompiledFunction({})", self.function().name)
+    }
+}
+
+/// Functions are the same if they are the same entry of the same program.
+impl PartialEq for CompiledFunction {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.program, &other.program) && self.index == other.index
+    }
+}
@@ -0,0 +1,343 @@
+//! Turns parsed statements into bytecode for the VM.
+use crate::analysis::diagnostic::line_col;
+use crate::parser::ast::{Expr, Statement};
+use crate::parser::cst::{parse_source, SyntaxKind};
+use crate::parser::error::ParseError;
+use crate::vm::bytecode::{Constant, Function, Instruction, Program};
+use std::convert::TryFrom;
+use std::mem;
+
+/// Compiles a whole program. Anything the parser accepts compiles, errors are only raised when the program runs.
+///
+/// # Examples
+/// ```ignore
+/// let program = compile(&parse(&mut Lexer::new("1 + 2;").lex()).unwrap());
+/// assert_eq!(program.code.len(), 4);
+/// assert_eq!(program.code[3], Instruction::Return);
+/// ```
+pub fn compile(statements: &[Statement]) -> Program {
+    compile_with_lines(statements, vec![])
+}
+
+/// Compiles a program along with a line table, given the line of every statement in the order they appear in the source. Nested statements count, so the lines of a function's body come straight after the line of its definition.
+pub fn compile_with_lines(statements: &[Statement], mut lines: Vec<u32>) -> Program {
+    lines.reverse();
+    let mut compiler = Compiler {
+        program: Program::default(),
+        statement_lines: lines,
+        line: None,
+    };
+    compiler.block(statements);
+    compiler.emit(Instruction::Return);
+    compiler.program
+}
+
+/// Parses and compiles source, keeping track of which line each instruction came from.
+///
+/// # Examples
+/// ```ignore
+/// let program = compile_source("owo x = 1;\nx + 2;").unwrap();
+/// assert_eq!(line_at(&program.lines, 0), Some(1));
+/// assert_eq!(line_at(&program.lines, program.code.len() - 1), Some(2));
+/// ```
+pub fn compile_source(source: &str) -> Result<Program, ParseError> {
+    let (tree, statements) = parse_source(source)?;
+    let lines = tree
+        .descendants()
+        .iter()
+        .filter(|node| {
+            matches!(
+                node.kind(),
+                SyntaxKind::Let
+                    | SyntaxKind::Define
+                    | SyntaxKind::Return
+                    | SyntaxKind::IndexAssign
+                    | SyntaxKind::Break
+                    | SyntaxKind::Continue
+                    | SyntaxKind::ExpressionStatement
+            )
+        })
+        .map(|node| line_col(source, node.span().start).0 as u32)
+        .collect();
+    Ok(compile_with_lines(&statements, lines))
+}
+
+struct Compiler {
+    /// Code is emitted into program.code, which is swapped out while compiling a function. The same goes for program.lines.
+    program: Program,
+    /// The lines of the statements still to be compiled, the next one last.
+    statement_lines: Vec<u32>,
+    /// The line of the statement being compiled.
+    line: Option<u32>,
+}
+
+/// Operands are 32 bits, which no program read from source comes close to.
+fn operand(position: usize) -> u32 {
+    u32::try_from(position).expect("program is too large to compile")
+}
+
+impl Compiler {
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        self.program.code.push(instruction);
+        self.program.code.len() - 1
+    }
+
+    /// The position the next instruction will be emitted at.
+    fn here(&self) -> u32 {
+        operand(self.program.code.len())
+    }
+
+    /// Points an already emitted jump at the next instruction.
+    fn patch(&mut self, at: usize) {
+        let target = self.here();
+        match &mut self.program.code[at] {
+            Instruction::Jump(to)
+            | Instruction::JumpUnlessTrue(to)
+            | Instruction::Next { exit: to, .. }
+            | Instruction::EnterLoop { exit: to, .. } => *to = target,
+            instruction => panic!("{:?} is not a jump", instruction),
+        }
+    }
+
+    /// Records that the instructions emitted from here on come from a line.
+    fn mark_line(&mut self, line: u32) {
+        let position = self.here();
+        let lines = &mut self.program.lines;
+        match lines.last_mut() {
+            Some((_, last)) if *last == line => (),
+            Some((start, last)) if *start == position => *last = line,
+            _ => lines.push((position, line)),
+        }
+    }
+
+    fn constant(&mut self, constant: Constant) -> u32 {
+        let constants = &mut self.program.constants;
+        match constants.iter().position(|existing| *existing == constant) {
+            Some(position) => operand(position),
+            None => {
+                constants.push(constant);
+                operand(constants.len() - 1)
+            }
+        }
+    }
+
+    fn name(&mut self, name: &str) -> u32 {
+        self.constant(Constant::String(String::from(name)))
+    }
+
+    /// Compiles a body so it leaves the value of its last statement, or none if it is empty.
+    fn block(&mut self, statements: &[Statement]) {
+        let line = self.line;
+        if statements.is_empty() {
+            if let Some(line) = line {
+                self.mark_line(line);
+            }
+            self.emit(Instruction::Null);
+        }
+        for (position, statement) in statements.iter().enumerate() {
+            self.statement(statement);
+            if position + 1 < statements.len() {
+                self.emit(Instruction::Pop);
+            }
+        }
+        // Whatever follows the block belongs to the statement it is part of.
+        if let Some(line) = line {
+            self.line = Some(line);
+            self.mark_line(line);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        if let Some(line) = self.statement_lines.pop() {
+            self.line = Some(line);
+            self.mark_line(line);
+        }
+        match statement {
+            Statement::Let { name, value } => {
+                self.expr(value);
+                let name = self.name(name);
+                self.emit(Instruction::Store(name));
+            }
+            Statement::Define { func_name, func } => {
+                match func {
+                    Expr::Function { parameters, body } => {
+                        self.function(func_name, parameters, body)
+                    }
+                    func => self.expr(func),
+                }
+                let name = self.name(func_name);
+                self.emit(Instruction::Store(name));
+            }
+            Statement::IndexAssign { name, index, value } => {
+                self.expr(index);
+                self.expr(value);
+                let name = self.name(name);
+                self.emit(Instruction::SetIndex(name));
+            }
+            Statement::Return { value } => {
+                self.expr(value);
+                self.emit(Instruction::Return);
+            }
+            Statement::Break => {
+                self.emit(Instruction::Break);
+            }
+            Statement::Continue => {
+                self.emit(Instruction::Continue);
+            }
+            Statement::Expression(expr) => self.expr(expr),
+        }
+    }
+
+    /// Compiles a function into the function table and emits the instruction that creates it.
+    fn function(&mut self, name: &str, parameters: &[String], body: &[Statement]) {
+        let outer = mem::take(&mut self.program.code);
+        let outer_lines = mem::take(&mut self.program.lines);
+        self.block(body);
+        self.emit(Instruction::Return);
+        let code = mem::replace(&mut self.program.code, outer);
+        let lines = mem::replace(&mut self.program.lines, outer_lines);
+
+        let display = Expr::Function {
+            parameters: parameters.to_vec(),
+            body: body.to_vec(),
+        }
+        .to_string();
+        self.program.functions.push(Function {
+            name: String::from(name),
+            parameters: parameters.to_vec(),
+            display,
+            code,
+            lines,
+        });
+        let index = operand(self.program.functions.len() - 1);
+        self.emit(Instruction::Function(index));
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Integer(num) => {
+                let constant = self.constant(Constant::Integer(*num));
+                self.emit(Instruction::Constant(constant));
+            }
+            Expr::String(string) => {
+                let constant = self.constant(Constant::String(string.clone()));
+                self.emit(Instruction::Constant(constant));
+            }
+            Expr::Boolean(true) => {
+                self.emit(Instruction::True);
+            }
+            Expr::Boolean(false) => {
+                self.emit(Instruction::False);
+            }
+            Expr::Variable(name) => {
+                let name = self.name(name);
+                self.emit(Instruction::Load(name));
+            }
+            Expr::Array(elements) => {
+                elements.iter().for_each(|element| self.expr(element));
+                self.emit(Instruction::Array(operand(elements.len())));
+            }
+            // Keys are checked as soon as they are evaluated, before their value is.
+            Expr::Map(pairs) => {
+                for (key, value) in pairs {
+                    self.expr(key);
+                    self.emit(Instruction::MapKey);
+                    self.expr(value);
+                }
+                self.emit(Instruction::Map(operand(pairs.len())));
+            }
+            Expr::Index { left, index } => {
+                self.expr(left);
+                self.expr(index);
+                self.emit(Instruction::Index);
+            }
+            Expr::Prefix { prefix, value } => {
+                self.expr(value);
+                self.emit(Instruction::Prefix(*prefix));
+            }
+            Expr::Infix {
+                left,
+                operator,
+                right,
+            } => {
+                self.expr(left);
+                self.expr(right);
+                self.emit(Instruction::Infix(*operator));
+            }
+            Expr::If {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                self.expr(condition);
+                let to_alternative = self.emit(Instruction::JumpUnlessTrue(0));
+                self.block(consequence);
+                let to_end = self.emit(Instruction::Jump(0));
+                self.patch(to_alternative);
+                self.block(alternative);
+                self.patch(to_end);
+            }
+            // The loop is only entered around the body, as bweak in the condition belongs to a loop further out.
+            Expr::While {
+                condition,
+                instruction,
+            } => {
+                let start = self.here();
+                self.expr(condition);
+                let to_end = self.emit(Instruction::JumpUnlessTrue(0));
+                let enter = self.emit(Instruction::EnterLoop { exit: 0, next: 0 });
+                self.block(instruction);
+                self.emit(Instruction::Pop);
+                let next = self.here();
+                self.emit(Instruction::ExitLoop);
+                self.emit(Instruction::Jump(start));
+                let exit = self.here();
+                self.emit(Instruction::ExitLoop);
+                self.program.code[enter] = Instruction::EnterLoop { exit, next };
+                self.patch(to_end);
+                self.emit(Instruction::Null);
+            }
+            // The items and the position of the next one stay on the stack underneath the loop.
+            Expr::For {
+                variable,
+                iterable,
+                body,
+            } => {
+                self.expr(iterable);
+                self.emit(Instruction::Iterate);
+                let variable = self.name(variable);
+                self.emit(Instruction::EnterScope(variable));
+                let next = self.here() + 1;
+                let enter = self.emit(Instruction::EnterLoop { exit: 0, next });
+                let to_exit = self.emit(Instruction::Next { variable, exit: 0 });
+                self.block(body);
+                self.emit(Instruction::Pop);
+                self.emit(Instruction::Jump(next));
+                self.patch(enter);
+                self.patch(to_exit);
+                self.emit(Instruction::ExitLoop);
+                self.emit(Instruction::ExitScope);
+                self.emit(Instruction::Pop);
+                self.emit(Instruction::Pop);
+                self.emit(Instruction::Null);
+            }
+            Expr::Function { parameters, body } => self.function("function", parameters, body),
+            // Arguments are evaluated before the callee, like the tree-walker does.
+            Expr::Call {
+                function,
+                arguments,
+            } => {
+                arguments.iter().for_each(|argument| self.expr(argument));
+                let name = match function.as_ref() {
+                    Expr::Variable(name) => self.name(name),
+                    _ => self.name("function"),
+                };
+                self.expr(function);
+                self.emit(Instruction::Call {
+                    arguments: operand(arguments.len()),
+                    name,
+                });
+            }
+        }
+    }
+}
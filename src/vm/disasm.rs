@@ -0,0 +1,85 @@
+//! Prints compiled programs in a readable form, for debugging the compiler and the VM.
+use crate::vm::bytecode::{Constant, Instruction, Program};
+use std::fmt::Write;
+
+/// Lists the constants of a program, then its top level and each function's code. Operands that refer to something are followed by what they refer to, and lines from the line table head the instructions they cover.
+///
+/// # Examples
+/// ```ignore
+/// let listing = disassemble(&compile_source("owo x = 1;").unwrap());
+/// assert!(listing.contains("Store 1  ; x"));
+/// ```
+pub fn disassemble(program: &Program) -> String {
+    let mut listing = String::from("constants:\n");
+    for (index, constant) in program.constants.iter().enumerate() {
+        writeln!(listing, "{:>6}  {}", index, show_constant(constant)).unwrap();
+    }
+    listing.push_str("\nmain:\n");
+    block(&mut listing, program, &program.code, &program.lines);
+    for (index, function) in program.functions.iter().enumerate() {
+        writeln!(
+            listing,
+            "\nfunction {} {}({}):",
+            index,
+            function.name,
+            function.parameters.join(", ")
+        )
+        .unwrap();
+        block(&mut listing, program, &function.code, &function.lines);
+    }
+    listing
+}
+
+fn show_constant(constant: &Constant) -> String {
+    match constant {
+        Constant::Integer(num) => num.to_string(),
+        Constant::String(string) => format!("{:?}", string),
+    }
+}
+
+fn block(listing: &mut String, program: &Program, code: &[Instruction], lines: &[(u32, u32)]) {
+    let mut lines = lines.iter().peekable();
+    for (position, instruction) in code.iter().enumerate() {
+        while let Some((_, line)) = lines.next_if(|(start, _)| *start as usize <= position) {
+            writeln!(listing, "  line {}", line).unwrap();
+        }
+        let (text, note) = show_instruction(program, instruction);
+        match note {
+            Some(note) => writeln!(listing, "{:>6}  {}  ; {}", position, text, note),
+            None => writeln!(listing, "{:>6}  {}", position, text),
+        }
+        .unwrap();
+    }
+}
+
+/// Writes an instruction with its operands, along with a note on what they refer to.
+fn show_instruction(program: &Program, instruction: &Instruction) -> (String, Option<String>) {
+    let constant = |index: u32| show_constant(&program.constants[index as usize]);
+    let name = |index: u32| String::from(program.name(index));
+    match *instruction {
+        Instruction::Constant(index) => (format!("Constant {}", index), Some(constant(index))),
+        Instruction::Load(index) => (format!("Load {}", index), Some(name(index))),
+        Instruction::Store(index) => (format!("Store {}", index), Some(name(index))),
+        Instruction::SetIndex(index) => (format!("SetIndex {}", index), Some(name(index))),
+        Instruction::EnterScope(index) => (format!("EnterScope {}", index), Some(name(index))),
+        Instruction::Array(count) => (format!("Array {}", count), None),
+        Instruction::Map(count) => (format!("Map {}", count), None),
+        Instruction::Prefix(prefix) => (format!("Prefix {:?}", prefix), None),
+        Instruction::Infix(operator) => (format!("Infix {:?}", operator), None),
+        Instruction::Jump(target) => (format!("Jump {}", target), None),
+        Instruction::JumpUnlessTrue(target) => (format!("JumpUnlessTrue {}", target), None),
+        Instruction::Function(index) => (
+            format!("Function {}", index),
+            Some(program.functions[index as usize].name.clone()),
+        ),
+        Instruction::Call {
+            arguments,
+            name: index,
+        } => (format!("Call {} {}", arguments, index), Some(name(index))),
+        Instruction::EnterLoop { exit, next } => (format!("EnterLoop {} {}", exit, next), None),
+        Instruction::Next { variable, exit } => {
+            (format!("Next {} {}", variable, exit), Some(name(variable)))
+        }
+        instruction => (format!("{:?}", instruction), None),
+    }
+}
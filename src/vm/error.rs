@@ -0,0 +1,34 @@
+//! Errors raised while loading a compiled program.
+use std::error;
+use std::fmt;
+
+/// Describes why bytes could not be loaded as a compiled program.
+#[derive(Debug, PartialEq, Clone)]
+pub enum LoadError {
+    /// The bytes don't start with the magic header, so they aren't a compiled program at all.
+    NotCompiled,
+    /// The program was written by a version of uwucode that used a different format.
+    UnsupportedVersion(u16),
+    /// The bytes ended partway through the program.
+    Truncated,
+    /// The bytes have the right shape but describe something the VM can't run, i.e a jump out of the code.
+    Malformed(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::NotCompiled => "not a compiled uwucode program".fmt(f),
+            LoadError::UnsupportedVersion(version) => write!(
+                f,
+                "compiled with format version {}, this uwucode reads version {}",
+                version,
+                crate::vm::uwuc::VERSION
+            ),
+            LoadError::Truncated => "the compiled program ends early".fmt(f),
+            LoadError::Malformed(reason) => write!(f, "malformed compiled program, {}", reason),
+        }
+    }
+}
+
+impl error::Error for LoadError {}
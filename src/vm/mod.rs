@@ -0,0 +1,8 @@
+//! The vm module compiles parsed statements to bytecode and runs it, as an alternative to walking the syntax tree.
+
+pub mod bytecode;
+pub mod compiler;
+pub mod disasm;
+pub mod error;
+pub mod uwuc;
+pub mod vm;
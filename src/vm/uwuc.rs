@@ -0,0 +1,533 @@
+//! Reads and writes compiled programs, usually kept in .uwuc files.
+//!
+//! # Technical Information
+//! Numbers are little endian, counts and operands are 32 bits and strings are a count of bytes followed by UTF-8. A file is laid out as:
+//! ```text
+//! magic      b"UWUC"
+//! version    u16
+//! flags      u16, bit 0 is set if every block of code is followed by its line table
+//! constants  count, then a tag byte for each, 0 followed by an i64 or 1 followed by a string
+//! functions  count, then for each its name, parameters, display string and code
+//! main code
+//! ```
+//! Code is a count of instructions, each an opcode byte followed by its operands, and then the line table if the flags say there is one.
+use crate::parser::ast::{Operator, Prefix};
+use crate::vm::bytecode::{Constant, Function, Instruction, LineTable, Program};
+use crate::vm::error::LoadError;
+use std::convert::TryFrom;
+
+/// Every compiled program starts with these bytes.
+pub const MAGIC: &[u8; 4] = b"UWUC";
+/// Bumped whenever the format changes, older formats are not read.
+pub const VERSION: u16 = 1;
+
+const HAS_LINES: u16 = 1;
+
+const PREFIXES: [Prefix; 2] = [Prefix::Bang, Prefix::Minus];
+const OPERATORS: [Operator; 11] = [
+    Operator::Plus,
+    Operator::Minus,
+    Operator::Multiply,
+    Operator::Divide,
+    Operator::Modulo,
+    Operator::GreaterThan,
+    Operator::GreaterThanEqual,
+    Operator::LessThan,
+    Operator::LessThanEqual,
+    Operator::Equals,
+    Operator::NotEquals,
+];
+
+/// Checks whether bytes look like a compiled program rather than source.
+pub fn is_compiled(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Writes a program out, including its line tables if it has any.
+///
+/// # Examples
+/// ```ignore
+/// let program = compile_source("uwu f(x) { sugoi x; }; f(1);").unwrap();
+/// assert_eq!(decode(&encode(&program)), Ok(program));
+/// ```
+pub fn encode(program: &Program) -> Vec<u8> {
+    let has_lines = program.has_lines();
+    let mut writer = Writer {
+        bytes: MAGIC.to_vec(),
+        has_lines,
+    };
+    writer.u16(VERSION);
+    writer.u16(if has_lines { HAS_LINES } else { 0 });
+
+    writer.count(program.constants.len());
+    for constant in &program.constants {
+        match constant {
+            Constant::Integer(num) => {
+                writer.u8(0);
+                writer.bytes.extend_from_slice(&num.to_le_bytes());
+            }
+            Constant::String(string) => {
+                writer.u8(1);
+                writer.string(string);
+            }
+        }
+    }
+
+    writer.count(program.functions.len());
+    for function in &program.functions {
+        writer.string(&function.name);
+        writer.count(function.parameters.len());
+        function
+            .parameters
+            .iter()
+            .for_each(|parameter| writer.string(parameter));
+        writer.string(&function.display);
+        writer.code(&function.code, &function.lines);
+    }
+    writer.code(&program.code, &program.lines);
+    writer.bytes
+}
+
+/// Reads a program back in, checking that everything it refers to exists so the VM can run it safely.
+pub fn decode(bytes: &[u8]) -> Result<Program, LoadError> {
+    if !is_compiled(bytes) {
+        return Err(LoadError::NotCompiled);
+    }
+    let mut reader = Reader {
+        bytes,
+        position: MAGIC.len(),
+        has_lines: false,
+    };
+    let version = reader.u16()?;
+    if version != VERSION {
+        return Err(LoadError::UnsupportedVersion(version));
+    }
+    reader.has_lines = reader.u16()? & HAS_LINES != 0;
+
+    let mut program = Program::default();
+    for _ in 0..reader.u32()? {
+        let constant = match reader.u8()? {
+            0 => Constant::Integer(i64::from_le_bytes(reader.array()?)),
+            1 => Constant::String(reader.string()?),
+            tag => return Err(malformed(format!("unknown constant tag {}", tag))),
+        };
+        program.constants.push(constant);
+    }
+
+    for _ in 0..reader.u32()? {
+        let name = reader.string()?;
+        let mut parameters = vec![];
+        for _ in 0..reader.u32()? {
+            parameters.push(reader.string()?);
+        }
+        let display = reader.string()?;
+        let (code, lines) = reader.code()?;
+        program.functions.push(Function {
+            name,
+            parameters,
+            display,
+            code,
+            lines,
+        });
+    }
+    let (code, lines) = reader.code()?;
+    program.code = code;
+    program.lines = lines;
+
+    if reader.position != bytes.len() {
+        return Err(malformed(String::from("there are bytes after the program")));
+    }
+    validate(&program.code, &program)?;
+    for function in &program.functions {
+        validate(&function.code, &program)
+            .map_err(|error| malformed(format!("in function {}: {}", function.name, error)))?;
+    }
+    Ok(program)
+}
+
+fn malformed(reason: String) -> LoadError {
+    LoadError::Malformed(reason)
+}
+
+/// Checks that a block of code only refers to constants, names and functions that exist, can't run past its end and keeps its stack and loops balanced.
+fn validate(code: &[Instruction], program: &Program) -> Result<(), LoadError> {
+    if code.last() != Some(&Instruction::Return) {
+        return Err(malformed(String::from("code must end with a return")));
+    }
+    let constant = |index: u32| match program.constants.get(index as usize) {
+        Some(_) => Ok(()),
+        None => Err(malformed(format!("constant {} does not exist", index))),
+    };
+    let name = |index: u32| match program.constants.get(index as usize) {
+        Some(Constant::String(_)) => Ok(()),
+        _ => Err(malformed(format!("constant {} is not a name", index))),
+    };
+    let target = |target: u32| match (target as usize) < code.len() {
+        true => Ok(()),
+        false => Err(malformed(format!(
+            "jump to {} is outside of the code",
+            target
+        ))),
+    };
+    for instruction in code {
+        match *instruction {
+            Instruction::Constant(index) => constant(index)?,
+            Instruction::Load(index)
+            | Instruction::Store(index)
+            | Instruction::SetIndex(index)
+            | Instruction::EnterScope(index) => name(index)?,
+            Instruction::Call { name: index, .. } => name(index)?,
+            Instruction::Function(index) if index as usize >= program.functions.len() => {
+                return Err(malformed(format!("function {} does not exist", index)))
+            }
+            Instruction::Jump(to) | Instruction::JumpUnlessTrue(to) => target(to)?,
+            Instruction::EnterLoop { exit, next } => {
+                target(exit)?;
+                target(next)?;
+            }
+            Instruction::Next { variable, exit } => {
+                name(variable)?;
+                target(exit)?;
+            }
+            _ => (),
+        }
+    }
+    check_flow(code)
+}
+
+/// What is known about a value on the stack. Only fur loops care, everything else takes any value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Slot {
+    Value,
+    /// The items Iterate leaves for a fur loop.
+    Items,
+    /// The position of the next item, always on top of the items.
+    Position,
+}
+
+/// The state of a frame before an instruction runs.
+#[derive(Debug, Clone, PartialEq)]
+struct Shape {
+    stack: Vec<Slot>,
+    /// The stack height, exit and next of each loop that has been entered.
+    loops: Vec<(usize, u32, u32)>,
+    scopes: usize,
+}
+
+/// Follows every path through a block of code, checking that nothing pops more than the stack holds, that loops and scopes are only left after being entered, and that every path to an instruction leaves the stack the same way.
+///
+/// Operands must already have been checked, as jumps are followed without looking.
+fn check_flow(code: &[Instruction]) -> Result<(), LoadError> {
+    let mut seen: Vec<Option<Shape>> = vec![None; code.len()];
+    let start = Shape {
+        stack: vec![],
+        loops: vec![],
+        scopes: 0,
+    };
+    let mut pending = vec![(0, start)];
+    while let Some((position, mut shape)) = pending.pop() {
+        match &seen[position] {
+            Some(earlier) if *earlier == shape => continue,
+            Some(_) => {
+                return Err(malformed(format!(
+                    "paths to instruction {} leave the stack differently",
+                    position
+                )))
+            }
+            None => seen[position] = Some(shape.clone()),
+        }
+        let fail = |reason: &str| malformed(format!("instruction {} {}", position, reason));
+        let pop = |shape: &mut Shape, count: usize| match shape.stack.len().checked_sub(count) {
+            Some(height) => {
+                shape.stack.truncate(height);
+                Ok(())
+            }
+            None => Err(fail("pops more values than are on the stack")),
+        };
+        let mut next = vec![position + 1];
+        match code[position] {
+            Instruction::Constant(_)
+            | Instruction::True
+            | Instruction::False
+            | Instruction::Null
+            | Instruction::Load(_)
+            | Instruction::Function(_) => shape.stack.push(Slot::Value),
+            Instruction::Pop => pop(&mut shape, 1)?,
+            Instruction::Store(_) | Instruction::MapKey | Instruction::Prefix(_) => {
+                pop(&mut shape, 1)?;
+                shape.stack.push(Slot::Value);
+            }
+            Instruction::SetIndex(_) | Instruction::Index | Instruction::Infix(_) => {
+                pop(&mut shape, 2)?;
+                shape.stack.push(Slot::Value);
+            }
+            Instruction::Array(count) => {
+                pop(&mut shape, count as usize)?;
+                shape.stack.push(Slot::Value);
+            }
+            Instruction::Map(count) => {
+                pop(&mut shape, 2 * count as usize)?;
+                shape.stack.push(Slot::Value);
+            }
+            Instruction::Call { arguments, .. } => {
+                pop(&mut shape, arguments as usize + 1)?;
+                shape.stack.push(Slot::Value);
+            }
+            Instruction::Jump(to) => next = vec![to as usize],
+            Instruction::JumpUnlessTrue(to) => {
+                pop(&mut shape, 1)?;
+                next.push(to as usize);
+            }
+            Instruction::Return => {
+                pop(&mut shape, 1)?;
+                next.clear();
+            }
+            Instruction::EnterLoop { exit, next } => {
+                shape.loops.push((shape.stack.len(), exit, next));
+            }
+            Instruction::ExitLoop => {
+                shape
+                    .loops
+                    .pop()
+                    .ok_or_else(|| fail("leaves a loop it is not in"))?;
+            }
+            Instruction::Break | Instruction::Continue => {
+                let (height, exit, loop_next) = *shape
+                    .loops
+                    .last()
+                    .ok_or_else(|| fail("jumps out of a loop it is not in"))?;
+                shape.stack.truncate(height);
+                next = match code[position] {
+                    Instruction::Break => vec![exit as usize],
+                    _ => vec![loop_next as usize],
+                };
+            }
+            Instruction::Iterate => {
+                pop(&mut shape, 1)?;
+                shape.stack.extend([Slot::Items, Slot::Position]);
+            }
+            Instruction::EnterScope(_) => shape.scopes += 1,
+            Instruction::Next { exit, .. } => {
+                if !shape.stack.ends_with(&[Slot::Items, Slot::Position]) {
+                    return Err(fail("is not after the items of a fur loop"));
+                }
+                next.push(exit as usize);
+            }
+            Instruction::ExitScope => {
+                shape.scopes = shape
+                    .scopes
+                    .checked_sub(1)
+                    .ok_or_else(|| fail("leaves a scope it is not in"))?;
+            }
+        }
+        pending.extend(next.into_iter().map(|to| (to, shape.clone())));
+    }
+    Ok(())
+}
+
+/// Numbers each kind of instruction, the order must not change without bumping VERSION.
+fn opcode(instruction: &Instruction) -> u8 {
+    match instruction {
+        Instruction::Constant(_) => 0,
+        Instruction::True => 1,
+        Instruction::False => 2,
+        Instruction::Null => 3,
+        Instruction::Pop => 4,
+        Instruction::Load(_) => 5,
+        Instruction::Store(_) => 6,
+        Instruction::SetIndex(_) => 7,
+        Instruction::Index => 8,
+        Instruction::MapKey => 9,
+        Instruction::Array(_) => 10,
+        Instruction::Map(_) => 11,
+        Instruction::Prefix(_) => 12,
+        Instruction::Infix(_) => 13,
+        Instruction::Jump(_) => 14,
+        Instruction::JumpUnlessTrue(_) => 15,
+        Instruction::Function(_) => 16,
+        Instruction::Call { .. } => 17,
+        Instruction::Return => 18,
+        Instruction::EnterLoop { .. } => 19,
+        Instruction::ExitLoop => 20,
+        Instruction::Break => 21,
+        Instruction::Continue => 22,
+        Instruction::Iterate => 23,
+        Instruction::EnterScope(_) => 24,
+        Instruction::Next { .. } => 25,
+        Instruction::ExitScope => 26,
+    }
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+    has_lines: bool,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn count(&mut self, count: usize) {
+        self.u32(u32::try_from(count).expect("program is too large to write"));
+    }
+
+    fn string(&mut self, string: &str) {
+        self.count(string.len());
+        self.bytes.extend_from_slice(string.as_bytes());
+    }
+
+    fn code(&mut self, code: &[Instruction], lines: &[(u32, u32)]) {
+        self.count(code.len());
+        for instruction in code {
+            self.u8(opcode(instruction));
+            match *instruction {
+                Instruction::Constant(operand)
+                | Instruction::Load(operand)
+                | Instruction::Store(operand)
+                | Instruction::SetIndex(operand)
+                | Instruction::Array(operand)
+                | Instruction::Map(operand)
+                | Instruction::Jump(operand)
+                | Instruction::JumpUnlessTrue(operand)
+                | Instruction::Function(operand)
+                | Instruction::EnterScope(operand) => self.u32(operand),
+                Instruction::Prefix(prefix) => {
+                    self.u8(PREFIXES.iter().position(|p| *p == prefix).unwrap() as u8)
+                }
+                Instruction::Infix(operator) => {
+                    self.u8(OPERATORS.iter().position(|o| *o == operator).unwrap() as u8)
+                }
+                Instruction::Call { arguments, name } => {
+                    self.u32(arguments);
+                    self.u32(name);
+                }
+                Instruction::EnterLoop { exit, next } => {
+                    self.u32(exit);
+                    self.u32(next);
+                }
+                Instruction::Next { variable, exit } => {
+                    self.u32(variable);
+                    self.u32(exit);
+                }
+                _ => (),
+            }
+        }
+        if self.has_lines {
+            self.count(lines.len());
+            for (start, line) in lines {
+                self.u32(*start);
+                self.u32(*line);
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    has_lines: bool,
+}
+
+impl Reader<'_> {
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], LoadError> {
+        let end = self.position + N;
+        let slice = self
+            .bytes
+            .get(self.position..end)
+            .ok_or(LoadError::Truncated)?;
+        self.position = end;
+        Ok(<[u8; N]>::try_from(slice).expect("the slice has N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, LoadError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, LoadError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, LoadError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn string(&mut self) -> Result<String, LoadError> {
+        let length = self.u32()? as usize;
+        let end = self.position + length;
+        let slice = self
+            .bytes
+            .get(self.position..end)
+            .ok_or(LoadError::Truncated)?;
+        self.position = end;
+        String::from_utf8(slice.to_vec())
+            .map_err(|_| malformed(String::from("a string is not UTF-8")))
+    }
+
+    fn code(&mut self) -> Result<(Vec<Instruction>, LineTable), LoadError> {
+        let mut code = vec![];
+        for _ in 0..self.u32()? {
+            let instruction = match self.u8()? {
+                0 => Instruction::Constant(self.u32()?),
+                1 => Instruction::True,
+                2 => Instruction::False,
+                3 => Instruction::Null,
+                4 => Instruction::Pop,
+                5 => Instruction::Load(self.u32()?),
+                6 => Instruction::Store(self.u32()?),
+                7 => Instruction::SetIndex(self.u32()?),
+                8 => Instruction::Index,
+                9 => Instruction::MapKey,
+                10 => Instruction::Array(self.u32()?),
+                11 => Instruction::Map(self.u32()?),
+                12 => match PREFIXES.get(self.u8()? as usize) {
+                    Some(prefix) => Instruction::Prefix(*prefix),
+                    None => return Err(malformed(String::from("unknown prefix operator"))),
+                },
+                13 => match OPERATORS.get(self.u8()? as usize) {
+                    Some(operator) => Instruction::Infix(*operator),
+                    None => return Err(malformed(String::from("unknown infix operator"))),
+                },
+                14 => Instruction::Jump(self.u32()?),
+                15 => Instruction::JumpUnlessTrue(self.u32()?),
+                16 => Instruction::Function(self.u32()?),
+                17 => Instruction::Call {
+                    arguments: self.u32()?,
+                    name: self.u32()?,
+                },
+                18 => Instruction::Return,
+                19 => Instruction::EnterLoop {
+                    exit: self.u32()?,
+                    next: self.u32()?,
+                },
+                20 => Instruction::ExitLoop,
+                21 => Instruction::Break,
+                22 => Instruction::Continue,
+                23 => Instruction::Iterate,
+                24 => Instruction::EnterScope(self.u32()?),
+                25 => Instruction::Next {
+                    variable: self.u32()?,
+                    exit: self.u32()?,
+                },
+                26 => Instruction::ExitScope,
+                opcode => return Err(malformed(format!("unknown opcode {}", opcode))),
+            };
+            code.push(instruction);
+        }
+        let mut lines = vec![];
+        if self.has_lines {
+            for _ in 0..self.u32()? {
+                lines.push((self.u32()?, self.u32()?));
+            }
+        }
+        Ok((code, lines))
+    }
+}
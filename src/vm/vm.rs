@@ -0,0 +1,364 @@
+//! Runs compiled programs on a stack machine.
+//!
+//! Scopes are the same environments the tree-walker uses, so variables are still looked up by name and functions see the scope they are called from.
+use crate::eval::eval::{
+    apply_infix, apply_prefix, assign_index, call_object, eval_index, iterate, map_key, Context,
+    ControlFlow, Env, Object, RuntimeError,
+};
+use crate::eval::native::Arity;
+use crate::vm::bytecode::{CompiledFunction, Constant, Instruction, Program};
+use std::collections::BTreeMap;
+use std::mem;
+use std::rc::Rc;
+
+/// Runs a program in the given environment, returning the value of its last statement or the value given to a top level sugoi.
+///
+/// Like the tree-walker, the environment keeps whatever the program defined, and loop scopes are closed again if the program stops early.
+///
+/// # Examples
+/// ```ignore
+/// # use std::rc::Rc;
+/// let program = compile(&parse(&mut Lexer::new("uwu f(x) { sugoi x * 2; }; f(21);").lex()).unwrap());
+/// let result = run(Rc::new(program), &mut Env::new(), &mut Context::new());
+/// assert_eq!(result, Ok(Object::Integer(42)));
+/// ```
+pub fn run(program: Rc<Program>, env: &mut Env, ctx: &mut Context) -> Result<Object, ControlFlow> {
+    let mut vm = Vm {
+        env,
+        ctx,
+        stack: vec![],
+        frames: vec![Frame::new(program, None, 0, None)],
+    };
+    let result = vm.execute();
+    if result.is_err() {
+        vm.unwind();
+    }
+    result
+}
+
+/// Calls a compiled function from outside of the VM, i.e from the tree-walker or the host.
+pub fn call(
+    name: String,
+    function: CompiledFunction,
+    args: Vec<Object>,
+    env: &mut Env,
+    ctx: &mut Context,
+) -> Result<Object, ControlFlow> {
+    let mut vm = Vm {
+        env,
+        ctx,
+        stack: vec![],
+        frames: vec![],
+    };
+    vm.call(name, Object::Compiled(function), args)?;
+    let result = vm.execute();
+    if result.is_err() {
+        vm.unwind();
+    }
+    result
+}
+
+/// Where bweak and continyue go for the innermost loop.
+struct LoopRecord {
+    height: usize,
+    exit: usize,
+    next: usize,
+}
+
+/// A function being run, or the top level of the program.
+struct Frame {
+    program: Rc<Program>,
+    /// The position in the function table, or None for the top level.
+    function: Option<usize>,
+    ip: usize,
+    stack_base: usize,
+    /// The scope to go back to once the function returns.
+    caller_env: Option<Env>,
+    loops: Vec<LoopRecord>,
+    /// How many fur loop scopes are open in this frame.
+    scopes: usize,
+}
+
+impl Frame {
+    fn new(
+        program: Rc<Program>,
+        function: Option<usize>,
+        stack_base: usize,
+        caller_env: Option<Env>,
+    ) -> Self {
+        Frame {
+            program,
+            function,
+            ip: 0,
+            stack_base,
+            caller_env,
+            loops: vec![],
+            scopes: 0,
+        }
+    }
+
+    fn code(&self) -> &[Instruction] {
+        match self.function {
+            Some(index) => &self.program.functions[index].code,
+            None => &self.program.code,
+        }
+    }
+}
+
+struct Vm<'a> {
+    env: &'a mut Env,
+    ctx: &'a mut Context,
+    stack: Vec<Object>,
+    frames: Vec<Frame>,
+}
+
+impl Vm<'_> {
+    fn frame(&mut self) -> &mut Frame {
+        self.frames
+            .last_mut()
+            .expect("execution stops once every frame returns")
+    }
+
+    fn pop(&mut self) -> Object {
+        self.stack.pop().expect("the compiler balances the stack")
+    }
+
+    fn pop_many(&mut self, count: usize) -> Vec<Object> {
+        self.stack.split_off(self.stack.len() - count)
+    }
+
+    fn name(&self, index: u32) -> String {
+        let frame = self
+            .frames
+            .last()
+            .expect("execution stops once every frame returns");
+        String::from(frame.program.name(index))
+    }
+
+    fn jump(&mut self, target: u32) {
+        self.frame().ip = target as usize;
+    }
+
+    /// Closes the loop scope the current scope was opened inside of.
+    fn exit_scope(&mut self) {
+        let inner = mem::take(self.env);
+        *self.env = inner
+            .into_enclosing()
+            .expect("Loop scope lost its enclosing scope");
+    }
+
+    /// Leaves a frame, closing any loop scopes still open in it and going back to the caller's scope.
+    fn leave(&mut self, frame: Frame) {
+        for _ in 0..frame.scopes {
+            self.exit_scope();
+        }
+        if let Some(caller_env) = frame.caller_env {
+            *self.env = caller_env;
+            self.ctx.leave_call();
+        }
+        self.stack.truncate(frame.stack_base);
+    }
+
+    /// Leaves every frame after an error or termination, so the environment is the one run was given.
+    fn unwind(&mut self) {
+        while let Some(frame) = self.frames.pop() {
+            self.leave(frame);
+        }
+    }
+
+    fn execute(&mut self) -> Result<Object, ControlFlow> {
+        loop {
+            let frame = self.frame();
+            let instruction = frame.code()[frame.ip];
+            frame.ip += 1;
+            match instruction {
+                Instruction::Constant(index) => {
+                    let value = match &self.frame().program.constants[index as usize] {
+                        Constant::Integer(num) => Object::Integer(*num),
+                        Constant::String(string) => Object::String(string.clone()),
+                    };
+                    self.stack.push(value);
+                }
+                Instruction::True => self.stack.push(Object::Boolean(true)),
+                Instruction::False => self.stack.push(Object::Boolean(false)),
+                Instruction::Null => self.stack.push(Object::Null),
+                Instruction::Pop => {
+                    self.pop();
+                }
+                // Variables not bound in any scope may still name a native function
+                Instruction::Load(name) => {
+                    let name = self.name(name);
+                    let value = match self.env.get(&name) {
+                        Some(value) => value,
+                        None => match self.ctx.natives().get(&name) {
+                            Some(native) => Object::Native(native.clone()),
+                            None => return Err(RuntimeError::UndefinedVariable(name).into()),
+                        },
+                    };
+                    self.stack.push(value);
+                }
+                Instruction::Store(name) => {
+                    let name = self.name(name);
+                    let value = self.stack.last().expect("a value to store").clone();
+                    self.env.set(name, value);
+                }
+                Instruction::SetIndex(name) => {
+                    let name = self.name(name);
+                    let value = self.pop();
+                    let index = self.pop();
+                    let value = assign_index(name, index, value, self.env)?;
+                    self.stack.push(value);
+                }
+                Instruction::Index => {
+                    let index = self.pop();
+                    let left = self.pop();
+                    self.stack.push(eval_index(left, index)?);
+                }
+                Instruction::MapKey => {
+                    map_key(self.stack.last().expect("a key to check"))?;
+                }
+                Instruction::Array(count) => {
+                    let elements = self.pop_many(count as usize);
+                    self.stack.push(Object::Array(elements));
+                }
+                Instruction::Map(count) => {
+                    let mut items = self.pop_many(2 * count as usize).into_iter();
+                    let mut map = BTreeMap::new();
+                    while let (Some(key), Some(value)) = (items.next(), items.next()) {
+                        map.insert(map_key(&key)?, value);
+                    }
+                    self.stack.push(Object::Map(map));
+                }
+                Instruction::Prefix(prefix) => {
+                    let value = self.pop();
+                    self.stack.push(apply_prefix(prefix, value)?);
+                }
+                Instruction::Infix(operator) => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.stack.push(apply_infix(left, operator, right)?);
+                }
+                Instruction::Jump(target) => self.jump(target),
+                Instruction::JumpUnlessTrue(target) => {
+                    if self.pop() != Object::Boolean(true) {
+                        self.jump(target);
+                    }
+                }
+                Instruction::Function(index) => {
+                    let program = Rc::clone(&self.frame().program);
+                    let function = CompiledFunction::new(program, index as usize);
+                    self.stack.push(Object::Compiled(function));
+                }
+                Instruction::Call { arguments, name } => {
+                    let name = self.name(name);
+                    let callee = self.pop();
+                    let args = self.pop_many(arguments as usize);
+                    self.call(name, callee, args)?;
+                }
+                Instruction::Return => {
+                    let value = self.pop();
+                    let frame = self.frames.pop().expect("a frame to return from");
+                    self.leave(frame);
+                    // The frame execution started in has returned.
+                    if self.frames.is_empty() {
+                        return Ok(value);
+                    }
+                    self.stack.push(value);
+                }
+                Instruction::EnterLoop { exit, next } => {
+                    let height = self.stack.len();
+                    self.frame().loops.push(LoopRecord {
+                        height,
+                        exit: exit as usize,
+                        next: next as usize,
+                    });
+                }
+                Instruction::ExitLoop => {
+                    self.frame().loops.pop();
+                }
+                Instruction::Break | Instruction::Continue => {
+                    let frame = self.frames.last_mut().expect("a frame to loop in");
+                    let record = frame.loops.last().expect("the parser keeps bweak in loops");
+                    frame.ip = match instruction {
+                        Instruction::Break => record.exit,
+                        _ => record.next,
+                    };
+                    let height = record.height;
+                    self.stack.truncate(height);
+                }
+                Instruction::Iterate => {
+                    let items = iterate(self.pop())?;
+                    self.stack.push(Object::Array(items));
+                    self.stack.push(Object::Integer(0));
+                }
+                // The outer scope is moved into the loop scope rather than cloned, so definitions made in the body persist.
+                Instruction::EnterScope(variable) => {
+                    let variable = self.name(variable);
+                    let outer = mem::take(self.env);
+                    *self.env = Env::new_loop(outer, variable);
+                    self.frame().scopes += 1;
+                }
+                Instruction::Next { variable, exit } => {
+                    let variable = self.name(variable);
+                    let length = self.stack.len();
+                    let position = match self.stack[length - 1] {
+                        Object::Integer(position) => position as usize,
+                        _ => unreachable!("Iterate pushes the position of the next item"),
+                    };
+                    let item = match &self.stack[length - 2] {
+                        Object::Array(items) => items.get(position).cloned(),
+                        _ => unreachable!("Iterate pushes the items as an array"),
+                    };
+                    match item {
+                        Some(item) => {
+                            self.stack[length - 1] = Object::Integer(position as i64 + 1);
+                            self.env.set(variable, item);
+                        }
+                        None => self.jump(exit),
+                    }
+                }
+                Instruction::ExitScope => {
+                    self.exit_scope();
+                    self.frame().scopes -= 1;
+                }
+            }
+        }
+    }
+
+    /// Calls a function with arguments that have already been evaluated. Compiled functions get a frame of their own, anything else is called the way the tree-walker would.
+    fn call(&mut self, name: String, callee: Object, args: Vec<Object>) -> Result<(), ControlFlow> {
+        let function = match callee {
+            Object::Compiled(function) => function,
+            callee => {
+                let value = call_object(name, callee, args, self.env, self.ctx)?;
+                self.stack.push(value);
+                return Ok(());
+            }
+        };
+        self.ctx.enter_call()?;
+        let parameters = &function.function().parameters;
+        if args.len() != parameters.len() {
+            self.ctx.leave_call();
+            return Err(RuntimeError::WrongArity {
+                name,
+                expected: Arity::Exact(parameters.len()),
+                found: args.len(),
+            }
+            .into());
+        }
+
+        let mut func_env = Env::new_enclosing(self.env.clone());
+        for (param, arg) in parameters.iter().zip(args) {
+            func_env.set(param.clone(), arg);
+        }
+        let caller_env = mem::replace(self.env, func_env);
+        let frame = Frame::new(
+            Rc::clone(function.program()),
+            Some(function.index()),
+            self.stack.len(),
+            Some(caller_env),
+        );
+        self.frames.push(frame);
+        Ok(())
+    }
+}